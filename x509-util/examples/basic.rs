@@ -162,12 +162,11 @@ async fn leaf<'a>(
     let name = Name::from_pairs(context, &[("C", "US"), ("ST", "CA"), ("CN", "leafy")]).unwrap();
 
     let subject_pubkey_info = key_pair.subject_public_key_info(context).await.unwrap();
-    let ca_pubkey_info = ca_keypair.subject_public_key_info(context).await.unwrap();
 
     let extensions = vec![
         Extensions::basic_constraints(context, true, true, None).unwrap(),
         Extensions::key_usage(context, true, &[KeyUsages::KeyCertSign]).unwrap(),
-        Extensions::authority_key_identifier(context, false, ca_pubkey_info).unwrap(),
+        Extensions::authority_key_identifier_from_issuer(context, false, &ca).unwrap(),
         Extensions::subject_key_identifier(context, false, subject_pubkey_info).unwrap(),
     ];
 
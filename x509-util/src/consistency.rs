@@ -0,0 +1,384 @@
+//! Consistency checks of the extensions of a certificate before it is signed, following
+//! [RFC 5280 Section 4.2](https://www.rfc-editor.org/rfc/rfc5280#section-4.2)
+
+use std::fmt;
+
+use const_oid::ObjectIdentifier;
+use x509_cert::{ext::pkix::KeyUsages, TbsCertificate};
+
+use crate::{
+    context::Context,
+    error::{Error, Result},
+    extensions::{DecodedExtension, KeyPurpose},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Violates a MUST of the relevant RFC, the certificate should not be issued
+    Error,
+    /// Violates a SHOULD or is unlikely to be intended
+    Warning,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    PathLenWithoutCa,
+    PathLenWithoutKeyCertSign,
+    KeyCertSignWithoutCa,
+    CaWithoutKeyUsage,
+    CaWithoutKeyCertSign,
+    BasicConstraintsNotCritical,
+    KeyUsageNotCritical,
+    NameConstraintsWithoutCa,
+    NameConstraintsNotCritical,
+    /// The SubjectKeyIdentifier or AuthorityKeyIdentifier extension is marked critical
+    KeyIdentifierCritical(ObjectIdentifier),
+    /// The key usage can't be used with the algorithm of the subject public key
+    KeyUsageNotAllowedForKey(KeyUsages),
+    EncipherOnlyWithoutKeyAgreement,
+    /// None of the key usages fit the given extended key usage
+    ExtendedKeyUsageMismatch(KeyPurpose),
+    /// The value of the extension can't be decoded
+    MalformedExtension(ObjectIdentifier),
+}
+
+impl Issue {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Issue::CaWithoutKeyUsage
+            | Issue::CaWithoutKeyCertSign
+            | Issue::KeyUsageNotCritical
+            | Issue::ExtendedKeyUsageMismatch(_) => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::PathLenWithoutCa => write!(f, "pathLenConstraint is set but cA is not"),
+            Issue::PathLenWithoutKeyCertSign => {
+                write!(f, "pathLenConstraint is set but keyCertSign is not")
+            }
+            Issue::KeyCertSignWithoutCa => write!(f, "keyCertSign is set but cA is not"),
+            Issue::CaWithoutKeyUsage => write!(f, "CA certificate without KeyUsage"),
+            Issue::CaWithoutKeyCertSign => write!(f, "CA certificate without keyCertSign"),
+            Issue::BasicConstraintsNotCritical => {
+                write!(f, "BasicConstraints of a CA certificate must be critical")
+            }
+            Issue::KeyUsageNotCritical => write!(f, "KeyUsage should be critical"),
+            Issue::NameConstraintsWithoutCa => {
+                write!(f, "NameConstraints is only allowed in CA certificates")
+            }
+            Issue::NameConstraintsNotCritical => write!(f, "NameConstraints must be critical"),
+            Issue::KeyIdentifierCritical(oid) => {
+                write!(f, "Key identifier extension `{}` must not be critical", oid)
+            }
+            Issue::KeyUsageNotAllowedForKey(usage) => {
+                write!(f, "{:?} is not allowed for the subject key type", usage)
+            }
+            Issue::EncipherOnlyWithoutKeyAgreement => {
+                write!(f, "encipherOnly and decipherOnly require keyAgreement")
+            }
+            Issue::ExtendedKeyUsageMismatch(purpose) => write!(
+                f,
+                "None of the key usages are consistent with {:?}",
+                purpose
+            ),
+            Issue::MalformedExtension(oid) => write!(f, "Extension `{}` can't be decoded", oid),
+        }
+    }
+}
+
+/// Key usages allowed per subject public key algorithm, `None` for unknown algorithms
+fn allowed_key_usages(algorithm: ObjectIdentifier) -> Option<&'static [KeyUsages]> {
+    use KeyUsages::*;
+
+    Some(match algorithm {
+        // RFC 3279 Section 2.3.1
+        const_oid::db::rfc5912::RSA_ENCRYPTION => &[
+            DigitalSignature,
+            NonRepudiation,
+            KeyEncipherment,
+            DataEncipherment,
+            KeyCertSign,
+            CRLSign,
+        ],
+        // RFC 4055 Section 1.2
+        const_oid::db::rfc5912::ID_RSASSA_PSS => {
+            &[DigitalSignature, NonRepudiation, KeyCertSign, CRLSign]
+        }
+        // RFC 5480 Section 3
+        const_oid::db::rfc5912::ID_EC_PUBLIC_KEY => &[
+            DigitalSignature,
+            NonRepudiation,
+            KeyAgreement,
+            KeyCertSign,
+            CRLSign,
+            EncipherOnly,
+            DecipherOnly,
+        ],
+        // RFC 8410 Section 5
+        const_oid::db::rfc8410::ID_ED_25519 | const_oid::db::rfc8410::ID_ED_448 => {
+            &[DigitalSignature, NonRepudiation, KeyCertSign, CRLSign]
+        }
+        const_oid::db::rfc8410::ID_X_25519 | const_oid::db::rfc8410::ID_X_448 => {
+            &[KeyAgreement, EncipherOnly, DecipherOnly]
+        }
+        _ => return None,
+    })
+}
+
+/// Key usages of which at least one must be present for an extended key usage, see the notes
+/// of [RFC 5280 Section 4.2.1.12](https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.12)
+fn compatible_key_usages(purpose: KeyPurpose) -> &'static [KeyUsages] {
+    use KeyUsages::*;
+
+    match purpose {
+        KeyPurpose::ServerAuth => &[DigitalSignature, KeyEncipherment, KeyAgreement],
+        KeyPurpose::ClientAuth | KeyPurpose::SmartcardLogon | KeyPurpose::KdcAuth => {
+            &[DigitalSignature, KeyAgreement]
+        }
+        KeyPurpose::CodeSigning => &[DigitalSignature],
+        KeyPurpose::EmailProtection => &[
+            DigitalSignature,
+            NonRepudiation,
+            KeyEncipherment,
+            KeyAgreement,
+        ],
+        KeyPurpose::TimeStamping | KeyPurpose::OcspSigning => &[DigitalSignature, NonRepudiation],
+        KeyPurpose::Other(_) => &[],
+    }
+}
+
+/// Checks the extensions of `tbs` for consistency with each other and the subject public key.
+/// Returns every issue found, use [`ensure_consistent`] to reject on errors.
+pub fn check<'a>(context: &'a Context, tbs: &TbsCertificate<'a>) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    let mut is_ca = false;
+    let mut path_len = false;
+    let mut key_usages = None;
+    let mut purposes = Vec::new();
+    let mut name_constraints = None;
+
+    for extension in tbs.extensions.iter().flatten() {
+        let decoded = match DecodedExtension::from_x509(context, extension) {
+            Ok(decoded) => decoded,
+            Err(_) => {
+                issues.push(Issue::MalformedExtension(extension.extn_id));
+                continue;
+            }
+        };
+
+        match decoded {
+            DecodedExtension::BasicConstraints {
+                ca,
+                path_len_constraint,
+            } => {
+                if ca && !extension.critical {
+                    issues.push(Issue::BasicConstraintsNotCritical);
+                }
+
+                is_ca = ca;
+                path_len = path_len_constraint.is_some();
+            }
+            DecodedExtension::KeyUsage(usages) => {
+                if !extension.critical {
+                    issues.push(Issue::KeyUsageNotCritical);
+                }
+
+                key_usages = Some(usages);
+            }
+            DecodedExtension::ExtendedKeyUsage(p) => purposes = p,
+            DecodedExtension::SubjectKeyIdentifier(_)
+            | DecodedExtension::AuthorityKeyIdentifier { .. }
+                if extension.critical =>
+            {
+                issues.push(Issue::KeyIdentifierCritical(extension.extn_id));
+            }
+            // Subtrees the typed variant can't represent are still name constraints
+            DecodedExtension::NameConstraints { .. } | DecodedExtension::Unknown(_)
+                if extension.extn_id == const_oid::db::rfc5280::ID_CE_NAME_CONSTRAINTS =>
+            {
+                name_constraints = Some(extension.critical);
+            }
+            _ => {}
+        }
+    }
+
+    let key_cert_sign = key_usages
+        .as_ref()
+        .is_some_and(|u| u.contains(&KeyUsages::KeyCertSign));
+
+    if path_len && !is_ca {
+        issues.push(Issue::PathLenWithoutCa);
+    }
+    if path_len && is_ca && key_usages.is_some() && !key_cert_sign {
+        issues.push(Issue::PathLenWithoutKeyCertSign);
+    }
+    if key_cert_sign && !is_ca {
+        issues.push(Issue::KeyCertSignWithoutCa);
+    }
+    if let Some(critical) = name_constraints {
+        if !critical {
+            issues.push(Issue::NameConstraintsNotCritical);
+        }
+        if !is_ca {
+            issues.push(Issue::NameConstraintsWithoutCa);
+        }
+    }
+
+    match key_usages {
+        None if is_ca => issues.push(Issue::CaWithoutKeyUsage),
+        None => {}
+        Some(ref usages) => {
+            if is_ca && !key_cert_sign {
+                issues.push(Issue::CaWithoutKeyCertSign);
+            }
+
+            if let Some(allowed) = allowed_key_usages(tbs.subject_public_key_info.algorithm.oid) {
+                issues.extend(
+                    usages
+                        .iter()
+                        .filter(|u| !allowed.contains(u))
+                        .map(|u| Issue::KeyUsageNotAllowedForKey(*u)),
+                );
+            }
+
+            let encipher_decipher = usages
+                .iter()
+                .any(|u| matches!(u, KeyUsages::EncipherOnly | KeyUsages::DecipherOnly));
+            if encipher_decipher && !usages.contains(&KeyUsages::KeyAgreement) {
+                issues.push(Issue::EncipherOnlyWithoutKeyAgreement);
+            }
+
+            for purpose in purposes {
+                let compatible = compatible_key_usages(purpose);
+
+                if !compatible.is_empty() && !usages.iter().any(|u| compatible.contains(u)) {
+                    issues.push(Issue::ExtendedKeyUsageMismatch(purpose));
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Like [`check`], but fails with [`Error::InconsistentExtensions`] if any issue is an error.
+/// Returns the remaining warnings.
+pub fn ensure_consistent<'a>(context: &'a Context, tbs: &TbsCertificate<'a>) -> Result<Vec<Issue>> {
+    let issues = check(context, tbs);
+
+    if issues.iter().any(|i| i.severity() == Severity::Error) {
+        return Err(Error::InconsistentExtensions(issues));
+    }
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use x509_cert::ext::pkix::KeyUsages;
+
+    use super::{check, ensure_consistent, Issue};
+    use crate::{
+        context::Context,
+        extensions::{Extensions, KeyPurpose},
+        general_name::GeneralSubtree,
+        testing::tbs_certificate,
+    };
+
+    #[test]
+    fn test_consistency() {
+        let context = Context::new();
+
+        let ca = tbs_certificate(
+            &context,
+            Some(vec![
+                Extensions::basic_constraints(&context, true, true, Some(0)).unwrap(),
+                Extensions::key_usage(
+                    &context,
+                    true,
+                    &[KeyUsages::KeyCertSign, KeyUsages::CRLSign],
+                )
+                .unwrap(),
+            ]),
+        );
+        assert_eq!(check(&context, &ca), []);
+
+        let leaf = tbs_certificate(
+            &context,
+            Some(vec![
+                Extensions::basic_constraints(&context, true, false, Some(0)).unwrap(),
+                Extensions::key_usage(
+                    &context,
+                    false,
+                    &[KeyUsages::KeyEncipherment, KeyUsages::KeyCertSign],
+                )
+                .unwrap(),
+                Extensions::extended_key_usage(&context, false, &[KeyPurpose::CodeSigning])
+                    .unwrap(),
+            ]),
+        );
+        assert_eq!(
+            check(&context, &leaf),
+            [
+                Issue::KeyUsageNotCritical,
+                Issue::PathLenWithoutCa,
+                Issue::KeyCertSignWithoutCa,
+                Issue::KeyUsageNotAllowedForKey(KeyUsages::KeyEncipherment),
+                Issue::ExtendedKeyUsageMismatch(KeyPurpose::CodeSigning),
+            ]
+        );
+        assert!(ensure_consistent(&context, &leaf).is_err());
+
+        let warning_only = tbs_certificate(
+            &context,
+            Some(vec![Extensions::basic_constraints(
+                &context, true, true, None,
+            )
+            .unwrap()]),
+        );
+        assert_eq!(
+            ensure_consistent(&context, &warning_only).unwrap(),
+            [Issue::CaWithoutKeyUsage]
+        );
+
+        let constrained_leaf = tbs_certificate(
+            &context,
+            Some(vec![Extensions::name_constraints(
+                &context,
+                false,
+                &[GeneralSubtree::DnsName("example.com")],
+                &[],
+            )
+            .unwrap()]),
+        );
+        assert_eq!(
+            check(&context, &constrained_leaf),
+            [
+                Issue::NameConstraintsNotCritical,
+                Issue::NameConstraintsWithoutCa
+            ]
+        );
+
+        let malformed = tbs_certificate(
+            &context,
+            Some(vec![x509_cert::ext::Extension {
+                extn_id: const_oid::db::rfc5280::ID_CE_KEY_USAGE,
+                critical: true,
+                extn_value: &[0x04, 0x00],
+            }]),
+        );
+        assert_eq!(
+            check(&context, &malformed),
+            [Issue::MalformedExtension(
+                const_oid::db::rfc5280::ID_CE_KEY_USAGE
+            )]
+        );
+        assert!(ensure_consistent(&context, &malformed).is_err());
+    }
+}
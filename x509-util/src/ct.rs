@@ -0,0 +1,431 @@
+use std::error::Error as StdError;
+
+use async_trait::async_trait;
+use const_oid::ObjectIdentifier;
+use der::{
+    asn1::{Null, OctetStringRef},
+    Decode, Encode,
+};
+use x509_cert::{ext, Certificate, TbsCertificate};
+
+use crate::{
+    context::{Alloc, Context},
+    error::{Error, Result},
+    signer::{Signable, Signer},
+};
+
+/// Critical extension marking a certificate as a precertificate
+pub const CT_PRECERT_POISON: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.11129.2.4.3");
+
+/// Extension embedding the SCTs returned by logs for the precertificate
+pub const CT_PRECERT_SCTS: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.11129.2.4.2");
+
+/// Version of a v1 SCT, the only one defined by RFC 6962
+const SCT_V1: u8 = 0;
+
+/// Length of a log ID, the SHA-256 hash of the log's public key
+pub const LOG_ID_LEN: usize = 32;
+
+/// A v1 SignedCertificateTimestamp, see
+/// [RFC 6962 Section 3.2](https://www.rfc-editor.org/rfc/rfc6962#section-3.2)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedCertificateTimestamp {
+    pub version: u8,
+    pub log_id: [u8; LOG_ID_LEN],
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub extensions: Vec<u8>,
+    /// TLS `HashAlgorithm`, e.g. 4 for SHA-256
+    pub hash_algorithm: u8,
+    /// TLS `SignatureAlgorithm`, e.g. 3 for ECDSA
+    pub signature_algorithm: u8,
+    pub signature: Vec<u8>,
+}
+
+impl SignedCertificateTimestamp {
+    /// Serializes the SCT with the TLS presentation language encoding
+    pub fn to_tls(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(47 + self.extensions.len() + self.signature.len());
+
+        out.push(self.version);
+        out.extend_from_slice(&self.log_id);
+        out.extend_from_slice(&self.timestamp.to_be_bytes());
+        write_opaque16(&mut out, &self.extensions)?;
+        out.push(self.hash_algorithm);
+        out.push(self.signature_algorithm);
+        write_opaque16(&mut out, &self.signature)?;
+
+        Ok(out)
+    }
+
+    pub fn from_tls(bytes: &[u8]) -> Result<Self> {
+        let mut reader = TlsReader(bytes);
+
+        let version = reader.read_u8()?;
+        if version != SCT_V1 {
+            return Err(Error::UnsupportedSctVersion(version));
+        }
+
+        let sct = SignedCertificateTimestamp {
+            version,
+            log_id: reader
+                .read(LOG_ID_LEN)?
+                .try_into()
+                .map_err(|_| Error::InvalidSctList)?,
+            timestamp: u64::from_be_bytes(
+                reader
+                    .read(8)?
+                    .try_into()
+                    .map_err(|_| Error::InvalidSctList)?,
+            ),
+            extensions: reader.read_opaque16()?.to_vec(),
+            hash_algorithm: reader.read_u8()?,
+            signature_algorithm: reader.read_u8()?,
+            signature: reader.read_opaque16()?.to_vec(),
+        };
+
+        reader.finish()?;
+
+        Ok(sct)
+    }
+
+    /// Encodes a `SignedCertificateTimestampList`, without the wrapping OCTET STRING
+    pub fn encode_list(scts: &[SignedCertificateTimestamp]) -> Result<Vec<u8>> {
+        if scts.is_empty() {
+            return Err(Error::InvalidSctList);
+        }
+
+        let mut list = Vec::new();
+        for sct in scts {
+            write_opaque16(&mut list, &sct.to_tls()?)?;
+        }
+
+        let mut out = Vec::with_capacity(list.len() + 2);
+        write_opaque16(&mut out, &list)?;
+
+        Ok(out)
+    }
+
+    /// Decodes a `SignedCertificateTimestampList`, without the wrapping OCTET STRING
+    pub fn decode_list(bytes: &[u8]) -> Result<Vec<SignedCertificateTimestamp>> {
+        let mut outer = TlsReader(bytes);
+        let mut list = TlsReader(outer.read_opaque16()?);
+        outer.finish()?;
+
+        let mut scts = Vec::new();
+        while !list.0.is_empty() {
+            scts.push(Self::from_tls(list.read_opaque16()?)?);
+        }
+
+        if scts.is_empty() {
+            return Err(Error::InvalidSctList);
+        }
+
+        Ok(scts)
+    }
+
+    /// Decodes the SCTs embedded in a certificate's SignedCertificateTimestampList extension
+    pub fn from_extension(
+        extension: &ext::Extension<'_>,
+    ) -> Result<Vec<SignedCertificateTimestamp>> {
+        if extension.extn_id != CT_PRECERT_SCTS {
+            return Err(Error::InvalidSctList);
+        }
+
+        Self::decode_list(OctetStringRef::from_der(extension.extn_value)?.as_bytes())
+    }
+}
+
+fn write_opaque16(out: &mut Vec<u8>, data: &[u8]) -> Result<()> {
+    let len: u16 = data.len().try_into().map_err(|_| Error::InvalidSctList)?;
+
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(data);
+
+    Ok(())
+}
+
+struct TlsReader<'a>(&'a [u8]);
+
+impl<'a> TlsReader<'a> {
+    fn read(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(Error::InvalidSctList);
+        }
+
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+
+        Ok(head)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read(1)?[0])
+    }
+
+    fn read_opaque16(&mut self) -> Result<&'a [u8]> {
+        let len = self.read(2)?;
+        let len = u16::from_be_bytes([len[0], len[1]]);
+
+        self.read(len.into())
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::InvalidSctList)
+        }
+    }
+}
+
+/// A Certificate Transparency log accepting precertificates
+#[async_trait]
+pub trait CtLog {
+    type Err: StdError + Send + Sync + 'static;
+
+    /// Submits a DER encoded precertificate chain, leaf first, as done by the `add-pre-chain`
+    /// endpoint of [RFC 6962 Section 4.1](https://www.rfc-editor.org/rfc/rfc6962#section-4.1)
+    async fn add_pre_chain(
+        &self,
+        chain: &[&[u8]],
+    ) -> std::result::Result<SignedCertificateTimestamp, Self::Err>;
+}
+
+fn has_ct_extension(tbs: &TbsCertificate<'_>) -> bool {
+    tbs.extensions
+        .iter()
+        .flatten()
+        .any(|e| e.extn_id == CT_PRECERT_POISON || e.extn_id == CT_PRECERT_SCTS)
+}
+
+/// Returns a copy of `tbs` with the critical poison extension appended
+pub fn precertificate<'a>(
+    context: &'a Context,
+    tbs: &TbsCertificate<'a>,
+) -> Result<TbsCertificate<'a>> {
+    if has_ct_extension(tbs) {
+        return Err(Error::UnexpectedCtExtension);
+    }
+
+    let mut precert = tbs.clone();
+    precert
+        .extensions
+        .get_or_insert_with(Vec::new)
+        .push(ext::Extension {
+            extn_id: CT_PRECERT_POISON,
+            critical: true,
+            extn_value: Null.to_vec()?.alloc_into(context),
+        });
+
+    Ok(precert)
+}
+
+/// Returns a copy of `tbs` with the SignedCertificateTimestampList extension appended, in the
+/// same position the poison extension takes in the precertificate
+pub fn with_scts<'a>(
+    context: &'a Context,
+    tbs: &TbsCertificate<'a>,
+    scts: &[SignedCertificateTimestamp],
+) -> Result<TbsCertificate<'a>> {
+    if has_ct_extension(tbs) {
+        return Err(Error::UnexpectedCtExtension);
+    }
+
+    let list = SignedCertificateTimestamp::encode_list(scts)?;
+    let bytes = OctetStringRef::new(&list)?.to_vec()?;
+
+    let mut cert = tbs.clone();
+    cert.extensions
+        .get_or_insert_with(Vec::new)
+        .push(ext::Extension {
+            extn_id: CT_PRECERT_SCTS,
+            critical: false,
+            extn_value: bytes.alloc_into(context),
+        });
+
+    Ok(cert)
+}
+
+/// Signs a precertificate for `tbs`, submits it to every log and signs the final certificate
+/// with the returned SCTs embedded. `issuer_chain` holds the DER encoded issuer certificates
+/// the logs need to validate the precertificate.
+pub async fn issue<'a, S, L>(
+    context: &'a Context,
+    tbs: &TbsCertificate<'a>,
+    signer: &S,
+    issuer_chain: &[&[u8]],
+    logs: &[L],
+) -> Result<Certificate<'a>>
+where
+    S: Signer + Sync + Send,
+    L: CtLog + Sync,
+{
+    let precert = precertificate(context, tbs)?.sign(context, signer).await?;
+    let precert_der = precert.to_vec()?;
+
+    let chain: Vec<&[u8]> = std::iter::once(precert_der.as_slice())
+        .chain(issuer_chain.iter().copied())
+        .collect();
+
+    let mut scts = Vec::with_capacity(logs.len());
+    for log in logs {
+        let sct = log
+            .add_pre_chain(&chain)
+            .await
+            .map_err(|e| Error::ErrorSubmittingPrecertificate(Box::new(e)))?;
+
+        scts.push(sct);
+    }
+
+    with_scts(context, tbs, &scts)?.sign(context, signer).await
+}
+
+/// Returns the DER encoding of a precertificate's TBSCertificate with the poison extension
+/// removed, which is what a log signs over
+pub fn precertificate_tbs(precert: &Certificate<'_>) -> Result<Vec<u8>> {
+    let mut tbs = precert.tbs_certificate.clone();
+
+    let extensions = tbs.extensions.get_or_insert_with(Vec::new);
+    let len = extensions.len();
+    extensions.retain(|e| !(e.extn_id == CT_PRECERT_POISON && e.critical));
+
+    if extensions.len() == len {
+        return Err(Error::MissingPoisonExtension);
+    }
+
+    if extensions.is_empty() {
+        tbs.extensions = None;
+    }
+
+    Ok(tbs.to_vec()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use der::{Decode, Encode};
+    use sha2::{Digest, Sha256};
+    use spki::{AlgorithmIdentifier, SubjectPublicKeyInfo};
+    use x509_cert::Certificate;
+
+    use super::{CtLog, SignedCertificateTimestamp, CT_PRECERT_POISON, CT_PRECERT_SCTS};
+    use crate::{context::Context, error::Error, testing::tbs_certificate};
+
+    /// Stands in for a log, "signing" the precertificate TBS with a plain SHA-256
+    struct MockLog {
+        log_id: [u8; 32],
+    }
+
+    #[async_trait]
+    impl CtLog for MockLog {
+        type Err = Error;
+
+        async fn add_pre_chain(
+            &self,
+            chain: &[&[u8]],
+        ) -> Result<SignedCertificateTimestamp, Error> {
+            let precert = Certificate::from_der(chain[0])?;
+            let tbs = super::precertificate_tbs(&precert)?;
+
+            Ok(SignedCertificateTimestamp {
+                version: 0,
+                log_id: self.log_id,
+                timestamp: 1_700_000_000_000,
+                extensions: vec![],
+                hash_algorithm: 4,
+                signature_algorithm: 3,
+                signature: Sha256::digest(tbs).to_vec(),
+            })
+        }
+    }
+
+    struct MockSigner;
+
+    #[async_trait]
+    impl crate::signer::Signer for MockSigner {
+        type Err = Error;
+
+        async fn sign<T: AsRef<[u8]> + Send>(&self, _data: T) -> Result<Vec<u8>, Error> {
+            Ok(vec![0; 8])
+        }
+
+        async fn signature_algorithm(&self) -> Result<AlgorithmIdentifier<'static>, Error> {
+            Ok(crate::algorithm_identifier::ECDSA_WITH_SHA256)
+        }
+
+        async fn subject_public_key_info<'a, 'b>(
+            &'a self,
+            _context: &'b Context,
+        ) -> Result<SubjectPublicKeyInfo<'b>, Error> {
+            Ok(SubjectPublicKeyInfo {
+                algorithm: crate::algorithm_identifier::ECDSA_WITH_SHA256,
+                subject_public_key: &[4, 1, 2, 3],
+            })
+        }
+    }
+
+    #[test]
+    fn test_sct_list_round_trip() {
+        let sct = SignedCertificateTimestamp {
+            version: 0,
+            log_id: [7; 32],
+            timestamp: 42,
+            extensions: vec![],
+            hash_algorithm: 4,
+            signature_algorithm: 3,
+            signature: vec![1, 2, 3],
+        };
+
+        let list = SignedCertificateTimestamp::encode_list(&[sct.clone(), sct.clone()]).unwrap();
+        // 2 byte list length, then each SCT prefixed by its own 2 byte length
+        assert_eq!(list[..4], [0, 0x68, 0, 0x32]);
+        assert_eq!(
+            SignedCertificateTimestamp::decode_list(&list).unwrap(),
+            [sct.clone(), sct.clone()]
+        );
+
+        assert!(SignedCertificateTimestamp::decode_list(&list[..list.len() - 1]).is_err());
+        assert!(SignedCertificateTimestamp::encode_list(&[]).is_err());
+
+        let v2 = SignedCertificateTimestamp { version: 1, ..sct }
+            .to_tls()
+            .unwrap();
+        assert!(matches!(
+            SignedCertificateTimestamp::from_tls(&v2),
+            Err(Error::UnsupportedSctVersion(1))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_issue_with_mock_log() {
+        let context = Context::new();
+        let tbs = tbs_certificate(&context, None);
+
+        let logs = [MockLog { log_id: [1; 32] }, MockLog { log_id: [2; 32] }];
+        let cert = super::issue(&context, &tbs, &MockSigner, &[], &logs)
+            .await
+            .unwrap();
+
+        let extensions = cert.tbs_certificate.extensions.as_ref().unwrap();
+        assert!(extensions.iter().all(|e| e.extn_id != CT_PRECERT_POISON));
+
+        let sct_ext = extensions
+            .iter()
+            .find(|e| e.extn_id == CT_PRECERT_SCTS)
+            .unwrap();
+        let scts = SignedCertificateTimestamp::from_extension(sct_ext).unwrap();
+        assert_eq!(scts.len(), 2);
+        assert_eq!(scts[1].log_id, [2; 32]);
+
+        // The log signed over the final TBSCertificate minus the SCT list
+        let mut unembedded = cert.tbs_certificate.clone();
+        unembedded.extensions = None;
+        assert_eq!(
+            scts[0].signature,
+            Sha256::digest(unembedded.to_vec().unwrap()).to_vec()
+        );
+    }
+}
@@ -14,6 +14,94 @@ pub enum Error {
     FailedBuildingNotBefore,
     #[error("Failed building valifity not_after")]
     FailedBuildingNotAfter,
+    #[error("GeneralNames must contain at least one entry")]
+    EmptyGeneralNames,
+    #[error("ExtendedKeyUsage must contain at least one key purpose")]
+    EmptyExtendedKeyUsage,
+    #[error("NameConstraints must contain permitted or excluded subtrees")]
+    EmptyNameConstraints,
+    #[error("Info access extensions must contain at least one access description")]
+    EmptyAccessDescriptions,
+    #[error("CertificatePolicies must contain at least one policy")]
+    EmptyCertificatePolicies,
+    #[error("PolicyMappings must contain at least one mapping")]
+    EmptyPolicyMappings,
+    #[error("anyPolicy must not be mapped to or from another policy")]
+    AnyPolicyMapping,
+    #[error("PolicyConstraints must set requireExplicitPolicy or inhibitPolicyMapping")]
+    EmptyPolicyConstraints,
+    #[error("DisplayText must be between 1 and 200 characters, got {0}")]
+    InvalidDisplayText(usize),
+    #[error("Invalid SignedCertificateTimestampList")]
+    InvalidSctList,
+    #[error("Unsupported SCT version {0}, only v1 is supported")]
+    UnsupportedSctVersion(u8),
+    #[error("Certificate already carries a Certificate Transparency extension")]
+    UnexpectedCtExtension,
+    #[error("Precertificate is missing the poison extension")]
+    MissingPoisonExtension,
+    #[error("Error while submitting precertificate")]
+    ErrorSubmittingPrecertificate(Box<dyn std::error::Error + Send + Sync>),
+    #[error("TLS Feature must contain at least one feature")]
+    EmptyTlsFeatures,
+    #[error("Duplicate extension `{0}`")]
+    DuplicateExtension(const_oid::ObjectIdentifier),
+    #[error("Invalid IP prefix length `{0}`")]
+    InvalidIpPrefix(u8),
+    #[error("IP address must be 4 or 16 octets, got {0}")]
+    InvalidIpAddressLength(usize),
+    #[error("Unsupported GeneralName choice")]
+    UnsupportedGeneralName,
+    #[error("Netmask must be a contiguous prefix")]
+    InvalidNetmask,
+    #[error("GeneralSubtree minimum and maximum must be absent")]
+    UnsupportedSubtreeBounds,
+    #[error("Invalid SID `{0}`, expected the S-1-... string form")]
+    InvalidSid(String),
+    #[error("QCStatements must contain at least one statement")]
+    EmptyQcStatements,
+    #[error("Malformed statementInfo of a known QCStatement")]
+    InvalidQcStatement,
+    #[error("PDS language must be a two letter ISO 639-1 code, got `{0}`")]
+    InvalidPdsLanguage(String),
+    #[error("PSD2 strings must be between 1 and 256 characters, got {0}")]
+    InvalidPsd2String(usize),
+    #[error("Invalid RFC 4514 distinguished name `{0}`")]
+    InvalidRfc4514(String),
+    #[error("`{1}` can't be encoded as {0:?}String")]
+    InvalidStringEncoding(crate::name::StringEncoding, String),
+    #[error("RelativeDistinguishedName must contain at least one attribute")]
+    EmptyRelativeDistinguishedName,
+    #[error("Unknown attribute type `{0}`")]
+    UnknownAttributeType(String),
+    #[error("{0} must be at most {1} characters, got {2}")]
+    AttributeTooLong(&'static str, usize, usize),
+    #[error("Invalid {0} `{1}`, {2}")]
+    InvalidAttributeValue(&'static str, String, &'static str),
+    #[error("Invalid time `{0}`, expected RFC 3339, YYYY-MM-DD, now, today or a ±<duration>")]
+    InvalidTimeSpec(String),
+    #[error("`{0}` is before 1970, which can't be encoded")]
+    UnsupportedTime(chrono::DateTime<chrono::Utc>),
+    #[error("`{0}` can't be represented as a chrono DateTime")]
+    UnrepresentableTime(x509_cert::time::Time),
+    #[error("not_after `{1}` is before not_before `{0}`")]
+    NotAfterBeforeNotBefore(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>),
+    #[error("Validity of {0} seconds exceeds the maximum of {1} seconds")]
+    LifetimeExceedsMaximum(i64, i64),
+    #[error("not_before `{0}` is before the issuer's not_before `{1}`")]
+    NotBeforeBeforeIssuer(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>),
+    #[error("not_after `{0}` is after the issuer's not_after `{1}`")]
+    NotAfterAfterIssuer(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>),
+    #[error("Inconsistent extensions: {}", display_issues(.0))]
+    InconsistentExtensions(Vec<crate::consistency::Issue>),
+}
+
+fn display_issues(issues: &[crate::consistency::Issue]) -> String {
+    issues
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,16 +1,234 @@
-use der::{asn1::OctetStringRef, Encode};
+use const_oid::ObjectIdentifier;
+use der::{asn1::OctetStringRef, Decode, Encode};
 use flagset::FlagSet;
-use x509_cert::ext::{self, pkix::KeyUsages};
+use x509_cert::{
+    ext::{self, pkix::KeyUsages},
+    Certificate,
+};
 
 use crate::{
     context::{Alloc, Context},
     error::{Error, Result},
-    subject_public_key_info::SubjectPublicKeyInfo,
+    general_name::{GeneralName, GeneralSubtree},
+    subject_public_key_info::{KeyIdentifierMethod, SubjectPublicKeyInfo},
 };
 
+mod decoded;
+pub mod microsoft;
+mod policy;
+mod qc;
+mod set;
+
+pub use self::decoded::DecodedExtension;
+pub use self::policy::{CertificatePolicy, NoticeReference, PolicyQualifier};
+pub use self::qc::{PdsLocation, Psd2Role, QcStatement, QcType, ID_PE_QC_STATEMENTS};
+pub use self::set::ExtensionSet;
+
+/// Key purposes for the ExtendedKeyUsage extension, see
+/// [RFC 5280 Section 4.2.1.12](https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.12)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyPurpose {
+    ServerAuth,
+    ClientAuth,
+    CodeSigning,
+    EmailProtection,
+    TimeStamping,
+    OcspSigning,
+    /// Microsoft smart card logon, `szOID_KP_SMARTCARD_LOGON`
+    SmartcardLogon,
+    /// Kerberos KDC authentication, `id-pkinit-KPKdc` from RFC 4556
+    KdcAuth,
+    Other(ObjectIdentifier),
+}
+
+impl KeyPurpose {
+    pub fn oid(&self) -> ObjectIdentifier {
+        match self {
+            KeyPurpose::ServerAuth => const_oid::db::rfc5280::ID_KP_SERVER_AUTH,
+            KeyPurpose::ClientAuth => const_oid::db::rfc5280::ID_KP_CLIENT_AUTH,
+            KeyPurpose::CodeSigning => const_oid::db::rfc5280::ID_KP_CODE_SIGNING,
+            KeyPurpose::EmailProtection => const_oid::db::rfc5280::ID_KP_EMAIL_PROTECTION,
+            KeyPurpose::TimeStamping => const_oid::db::rfc5280::ID_KP_TIME_STAMPING,
+            KeyPurpose::OcspSigning => const_oid::db::rfc5280::ID_KP_OCSP_SIGNING,
+            KeyPurpose::SmartcardLogon => ID_KP_SMARTCARD_LOGON,
+            KeyPurpose::KdcAuth => ID_PKINIT_KP_KDC,
+            KeyPurpose::Other(oid) => *oid,
+        }
+    }
+}
+
+impl From<ObjectIdentifier> for KeyPurpose {
+    fn from(oid: ObjectIdentifier) -> Self {
+        match oid {
+            const_oid::db::rfc5280::ID_KP_SERVER_AUTH => KeyPurpose::ServerAuth,
+            const_oid::db::rfc5280::ID_KP_CLIENT_AUTH => KeyPurpose::ClientAuth,
+            const_oid::db::rfc5280::ID_KP_CODE_SIGNING => KeyPurpose::CodeSigning,
+            const_oid::db::rfc5280::ID_KP_EMAIL_PROTECTION => KeyPurpose::EmailProtection,
+            const_oid::db::rfc5280::ID_KP_TIME_STAMPING => KeyPurpose::TimeStamping,
+            const_oid::db::rfc5280::ID_KP_OCSP_SIGNING => KeyPurpose::OcspSigning,
+            ID_KP_SMARTCARD_LOGON => KeyPurpose::SmartcardLogon,
+            ID_PKINIT_KP_KDC => KeyPurpose::KdcAuth,
+            oid => KeyPurpose::Other(oid),
+        }
+    }
+}
+
+const ID_KP_SMARTCARD_LOGON: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.20.2.2");
+const ID_PKINIT_KP_KDC: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.2.3.5");
+
+/// Entries of the AuthorityInfoAccess extension, see
+/// [RFC 5280 Section 4.2.2.1](https://www.rfc-editor.org/rfc/rfc5280#section-4.2.2.1)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorityInfoAccess<'a> {
+    Ocsp(GeneralName<'a>),
+    CaIssuers(GeneralName<'a>),
+}
+
+impl<'a> AuthorityInfoAccess<'a> {
+    fn to_x509(&self, context: &'a Context) -> Result<ext::pkix::AccessDescription<'a>> {
+        let (access_method, location) = match self {
+            AuthorityInfoAccess::Ocsp(location) => (const_oid::db::rfc5280::ID_AD_OCSP, location),
+            AuthorityInfoAccess::CaIssuers(location) => {
+                (const_oid::db::rfc5280::ID_AD_CA_ISSUERS, location)
+            }
+        };
+
+        Ok(ext::pkix::AccessDescription {
+            access_method,
+            access_location: location.to_x509(context)?,
+        })
+    }
+
+    /// `None` for unknown access methods
+    fn from_x509(
+        context: &'a Context,
+        description: &ext::pkix::AccessDescription<'a>,
+    ) -> Result<Option<Self>> {
+        let location = GeneralName::from_x509(context, &description.access_location)?;
+
+        Ok(match description.access_method {
+            const_oid::db::rfc5280::ID_AD_OCSP => Some(AuthorityInfoAccess::Ocsp(location)),
+            const_oid::db::rfc5280::ID_AD_CA_ISSUERS => {
+                Some(AuthorityInfoAccess::CaIssuers(location))
+            }
+            _ => None,
+        })
+    }
+}
+
+/// Entries of the SubjectInfoAccess extension, see
+/// [RFC 5280 Section 4.2.2.2](https://www.rfc-editor.org/rfc/rfc5280#section-4.2.2.2)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubjectInfoAccess<'a> {
+    CaRepository(GeneralName<'a>),
+    TimeStamping(GeneralName<'a>),
+}
+
+impl<'a> SubjectInfoAccess<'a> {
+    fn to_x509(&self, context: &'a Context) -> Result<ext::pkix::AccessDescription<'a>> {
+        let (access_method, location) = match self {
+            SubjectInfoAccess::CaRepository(location) => {
+                (const_oid::db::rfc5280::ID_AD_CA_REPOSITORY, location)
+            }
+            SubjectInfoAccess::TimeStamping(location) => {
+                (const_oid::db::rfc5280::ID_AD_TIME_STAMPING, location)
+            }
+        };
+
+        Ok(ext::pkix::AccessDescription {
+            access_method,
+            access_location: location.to_x509(context)?,
+        })
+    }
+
+    /// `None` for unknown access methods
+    fn from_x509(
+        context: &'a Context,
+        description: &ext::pkix::AccessDescription<'a>,
+    ) -> Result<Option<Self>> {
+        let location = GeneralName::from_x509(context, &description.access_location)?;
+
+        Ok(match description.access_method {
+            const_oid::db::rfc5280::ID_AD_CA_REPOSITORY => {
+                Some(SubjectInfoAccess::CaRepository(location))
+            }
+            const_oid::db::rfc5280::ID_AD_TIME_STAMPING => {
+                Some(SubjectInfoAccess::TimeStamping(location))
+            }
+            _ => None,
+        })
+    }
+}
+
+/// TLS extension types for the TLS Feature extension, see
+/// [RFC 7633 Section 4.2](https://www.rfc-editor.org/rfc/rfc7633#section-4.2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TlsFeature {
+    /// `status_request`, better known as OCSP must-staple
+    StatusRequest,
+    /// `status_request_v2`
+    StatusRequestV2,
+    Other(u16),
+}
+
+impl TlsFeature {
+    pub fn extension_type(&self) -> u16 {
+        match self {
+            TlsFeature::StatusRequest => 5,
+            TlsFeature::StatusRequestV2 => 17,
+            TlsFeature::Other(t) => *t,
+        }
+    }
+}
+
+impl From<u16> for TlsFeature {
+    fn from(extension_type: u16) -> Self {
+        match extension_type {
+            5 => TlsFeature::StatusRequest,
+            17 => TlsFeature::StatusRequestV2,
+            t => TlsFeature::Other(t),
+        }
+    }
+}
+
+/// RFC 7633 `id-pe-tlsfeature`
+pub const ID_PE_TLS_FEATURE: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.1.24");
+
 pub struct Extensions;
 
 impl Extensions {
+    /// Builds an extension from an already DER encoded `extnValue`
+    pub fn raw<'a>(
+        context: &'a Context,
+        extn_id: ObjectIdentifier,
+        critical: bool,
+        der_value: &[u8],
+    ) -> Result<ext::Extension<'a>> {
+        // Make sure the value is a single, well formed DER element
+        der::AnyRef::from_der(der_value)?;
+
+        Ok(ext::Extension {
+            extn_id,
+            critical,
+            extn_value: der_value.alloc_into(context),
+        })
+    }
+
+    /// Builds an extension from any value that can be DER encoded
+    pub fn encoded<'a, T: Encode>(
+        context: &'a Context,
+        extn_id: ObjectIdentifier,
+        critical: bool,
+        value: &T,
+    ) -> Result<ext::Extension<'a>> {
+        Ok(ext::Extension {
+            extn_id,
+            critical,
+            extn_value: value.to_vec()?.alloc_into(context),
+        })
+    }
+
     pub fn basic_constraints(
         context: &Context,
         critical: bool,
@@ -49,12 +267,281 @@ impl Extensions {
         })
     }
 
+    pub fn extended_key_usage<'a>(
+        context: &'a Context,
+        critical: bool,
+        purposes: &[KeyPurpose],
+    ) -> Result<ext::Extension<'a>> {
+        if purposes.is_empty() {
+            return Err(Error::EmptyExtendedKeyUsage);
+        }
+
+        let bytes =
+            ext::pkix::ExtendedKeyUsage(purposes.iter().map(KeyPurpose::oid).collect()).to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_EXT_KEY_USAGE,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    pub fn name_constraints<'a>(
+        context: &'a Context,
+        critical: bool,
+        permitted: &[GeneralSubtree<'a>],
+        excluded: &[GeneralSubtree<'a>],
+    ) -> Result<ext::Extension<'a>> {
+        if permitted.is_empty() && excluded.is_empty() {
+            return Err(Error::EmptyNameConstraints);
+        }
+
+        let to_subtrees = |subtrees: &[GeneralSubtree<'a>]| -> Result<_> {
+            if subtrees.is_empty() {
+                return Ok(None);
+            }
+
+            subtrees
+                .iter()
+                .map(|s| s.to_x509(context))
+                .collect::<Result<Vec<_>>>()
+                .map(Some)
+        };
+
+        let bytes = ext::pkix::NameConstraints {
+            permitted_subtrees: to_subtrees(permitted)?,
+            excluded_subtrees: to_subtrees(excluded)?,
+        }
+        .to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_NAME_CONSTRAINTS,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    /// Builds a single DistributionPoint whose `fullName` holds all of `names`,
+    /// typically the URIs the CRL can be fetched from
+    pub fn crl_distribution_points<'a>(
+        context: &'a Context,
+        critical: bool,
+        names: &[GeneralName<'a>],
+    ) -> Result<ext::Extension<'a>> {
+        if names.is_empty() {
+            return Err(Error::EmptyGeneralNames);
+        }
+
+        let distribution_point = ext::pkix::crl::dp::DistributionPoint {
+            distribution_point: Some(ext::pkix::name::DistributionPointName::FullName(
+                GeneralName::to_x509_names(context, names)?,
+            )),
+            reasons: None,
+            crl_issuer: None,
+        };
+        let bytes = ext::pkix::CrlDistributionPoints(vec![distribution_point]).to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_CRL_DISTRIBUTION_POINTS,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    /// Always non-critical, as required by RFC 5280
+    pub fn authority_info_access<'a>(
+        context: &'a Context,
+        descriptions: &[AuthorityInfoAccess<'a>],
+    ) -> Result<ext::Extension<'a>> {
+        if descriptions.is_empty() {
+            return Err(Error::EmptyAccessDescriptions);
+        }
+
+        let bytes = ext::pkix::AuthorityInfoAccessSyntax(
+            descriptions
+                .iter()
+                .map(|d| d.to_x509(context))
+                .collect::<Result<_>>()?,
+        )
+        .to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_PE_AUTHORITY_INFO_ACCESS,
+            critical: false,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    /// Always non-critical, as required by RFC 5280
+    pub fn subject_info_access<'a>(
+        context: &'a Context,
+        descriptions: &[SubjectInfoAccess<'a>],
+    ) -> Result<ext::Extension<'a>> {
+        if descriptions.is_empty() {
+            return Err(Error::EmptyAccessDescriptions);
+        }
+
+        let bytes = ext::pkix::SubjectInfoAccessSyntax(
+            descriptions
+                .iter()
+                .map(|d| d.to_x509(context))
+                .collect::<Result<_>>()?,
+        )
+        .to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_PE_SUBJECT_INFO_ACCESS,
+            critical: false,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    pub fn certificate_policies<'a>(
+        context: &'a Context,
+        critical: bool,
+        policies: &[CertificatePolicy<'a>],
+    ) -> Result<ext::Extension<'a>> {
+        if policies.is_empty() {
+            return Err(Error::EmptyCertificatePolicies);
+        }
+
+        let bytes = ext::pkix::CertificatePolicies(
+            policies
+                .iter()
+                .map(|p| p.to_x509(context))
+                .collect::<Result<_>>()?,
+        )
+        .to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_CERTIFICATE_POLICIES,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    /// Each mapping is an `(issuerDomainPolicy, subjectDomainPolicy)` pair. Neither side may
+    /// be anyPolicy, as required by
+    /// [RFC 5280 Section 4.2.1.5](https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.5)
+    pub fn policy_mappings<'a>(
+        context: &'a Context,
+        critical: bool,
+        mappings: &[(ObjectIdentifier, ObjectIdentifier)],
+    ) -> Result<ext::Extension<'a>> {
+        if mappings.is_empty() {
+            return Err(Error::EmptyPolicyMappings);
+        }
+
+        if mappings.iter().any(|(issuer, subject)| {
+            *issuer == const_oid::db::rfc5280::ANY_POLICY
+                || *subject == const_oid::db::rfc5280::ANY_POLICY
+        }) {
+            return Err(Error::AnyPolicyMapping);
+        }
+
+        let bytes = ext::pkix::PolicyMappings(
+            mappings
+                .iter()
+                .map(|(issuer, subject)| ext::pkix::PolicyMapping {
+                    issuer_domain_policy: *issuer,
+                    subject_domain_policy: *subject,
+                })
+                .collect(),
+        )
+        .to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_POLICY_MAPPINGS,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    pub fn policy_constraints(
+        context: &Context,
+        critical: bool,
+        require_explicit_policy: Option<u32>,
+        inhibit_policy_mapping: Option<u32>,
+    ) -> Result<ext::Extension<'_>> {
+        if require_explicit_policy.is_none() && inhibit_policy_mapping.is_none() {
+            return Err(Error::EmptyPolicyConstraints);
+        }
+
+        let bytes = ext::pkix::PolicyConstraints {
+            require_explicit_policy,
+            inhibit_policy_mapping,
+        }
+        .to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_POLICY_CONSTRAINTS,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    pub fn inhibit_any_policy(
+        context: &Context,
+        critical: bool,
+        skip_certs: u32,
+    ) -> Result<ext::Extension<'_>> {
+        let bytes = ext::pkix::InhibitAnyPolicy(skip_certs).to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_INHIBIT_ANY_POLICY,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    /// Marks a delegated OCSP responder certificate as not needing revocation checks, see
+    /// [RFC 6960 Section 4.2.2.2.1](https://www.rfc-editor.org/rfc/rfc6960#section-4.2.2.2.1)
+    pub fn ocsp_no_check(context: &Context, critical: bool) -> Result<ext::Extension<'_>> {
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc6960::ID_PKIX_OCSP_NOCHECK,
+            critical,
+            extn_value: der::asn1::Null.to_vec()?.alloc_into(context),
+        })
+    }
+
+    pub fn tls_feature<'a>(
+        context: &'a Context,
+        critical: bool,
+        features: &[TlsFeature],
+    ) -> Result<ext::Extension<'a>> {
+        if features.is_empty() {
+            return Err(Error::EmptyTlsFeatures);
+        }
+
+        let types: Vec<u16> = features.iter().map(TlsFeature::extension_type).collect();
+
+        Ok(ext::Extension {
+            extn_id: ID_PE_TLS_FEATURE,
+            critical,
+            extn_value: types.to_vec()?.alloc_into(context),
+        })
+    }
+
     pub fn subject_key_identifier<'a>(
         context: &'a Context,
         critical: bool,
         pubkey_info: spki::SubjectPublicKeyInfo<'_>,
     ) -> Result<ext::Extension<'a>> {
-        let ident = SubjectPublicKeyInfo::as_key_identifier(pubkey_info);
+        Self::subject_key_identifier_with_method(
+            context,
+            critical,
+            pubkey_info,
+            KeyIdentifierMethod::default(),
+        )
+    }
+
+    pub fn subject_key_identifier_with_method<'a>(
+        context: &'a Context,
+        critical: bool,
+        pubkey_info: spki::SubjectPublicKeyInfo<'_>,
+        method: KeyIdentifierMethod,
+    ) -> Result<ext::Extension<'a>> {
+        let ident = SubjectPublicKeyInfo::as_key_identifier_with(pubkey_info, method);
         let octets = OctetStringRef::new(&ident)?;
         let bytes = ext::pkix::SubjectKeyIdentifier(octets).to_vec()?;
 
@@ -70,7 +557,22 @@ impl Extensions {
         critical: bool,
         pubkey_info: spki::SubjectPublicKeyInfo<'_>,
     ) -> Result<ext::Extension<'a>> {
-        let ident = SubjectPublicKeyInfo::as_key_identifier(pubkey_info);
+        Self::authority_key_identifier_with_method(
+            context,
+            critical,
+            pubkey_info,
+            KeyIdentifierMethod::default(),
+        )
+    }
+
+    /// `method` must match the one the issuer used for its SubjectKeyIdentifier
+    pub fn authority_key_identifier_with_method<'a>(
+        context: &'a Context,
+        critical: bool,
+        pubkey_info: spki::SubjectPublicKeyInfo<'_>,
+        method: KeyIdentifierMethod,
+    ) -> Result<ext::Extension<'a>> {
+        let ident = SubjectPublicKeyInfo::as_key_identifier_with(pubkey_info, method);
         let octets = OctetStringRef::new(&ident)?;
         let bytes = ext::pkix::AuthorityKeyIdentifier {
             key_identifier: Some(octets),
@@ -85,4 +587,388 @@ impl Extensions {
             extn_value: bytes.alloc_into(context),
         })
     }
+
+    /// Copies the key identifier from the issuer's SubjectKeyIdentifier extension, so chains
+    /// still link when the issuer used a different key identifier method. Falls back to
+    /// hashing the issuer's public key when it has no SubjectKeyIdentifier.
+    pub fn authority_key_identifier_from_issuer<'a>(
+        context: &'a Context,
+        critical: bool,
+        issuer: &Certificate<'_>,
+    ) -> Result<ext::Extension<'a>> {
+        let ident = issuer_key_identifier(issuer)?;
+        let bytes = ext::pkix::AuthorityKeyIdentifier {
+            key_identifier: Some(OctetStringRef::new(&ident)?),
+            authority_cert_issuer: None,
+            authority_cert_serial_number: None,
+        }
+        .to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_AUTHORITY_KEY_IDENTIFIER,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    /// Like [`Extensions::authority_key_identifier_from_issuer`], additionally filling
+    /// `authorityCertIssuer` and `authorityCertSerialNumber` with the issuer certificate's
+    /// own issuer name and serial number
+    pub fn authority_key_identifier_with_issuer_serial<'a>(
+        context: &'a Context,
+        critical: bool,
+        issuer: &Certificate<'_>,
+    ) -> Result<ext::Extension<'a>> {
+        let ident = issuer_key_identifier(issuer)?;
+        let bytes = ext::pkix::AuthorityKeyIdentifier {
+            key_identifier: Some(OctetStringRef::new(&ident)?),
+            authority_cert_issuer: Some(vec![ext::pkix::name::GeneralName::DirectoryName(
+                issuer.tbs_certificate.issuer.clone(),
+            )]),
+            authority_cert_serial_number: Some(issuer.tbs_certificate.serial_number),
+        }
+        .to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_AUTHORITY_KEY_IDENTIFIER,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+
+    /// The extension is marked critical when `subject` is empty, as required by
+    /// [RFC 5280 Section 4.2.1.6](https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.6)
+    pub fn subject_alt_name<'a>(
+        context: &'a Context,
+        subject: &x509_cert::name::Name<'_>,
+        names: &[GeneralName<'a>],
+    ) -> Result<ext::Extension<'a>> {
+        if names.is_empty() {
+            return Err(Error::EmptyGeneralNames);
+        }
+
+        let bytes =
+            ext::pkix::SubjectAltName(GeneralName::to_x509_names(context, names)?).to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: const_oid::db::rfc5280::ID_CE_SUBJECT_ALT_NAME,
+            critical: subject.0.is_empty(),
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+}
+
+fn issuer_key_identifier(issuer: &Certificate<'_>) -> Result<Vec<u8>> {
+    let ski = issuer
+        .tbs_certificate
+        .extensions
+        .iter()
+        .flatten()
+        .find(|e| e.extn_id == const_oid::db::rfc5280::ID_CE_SUBJECT_KEY_IDENTIFIER);
+
+    match ski {
+        Some(ski) => Ok(ext::pkix::SubjectKeyIdentifier::from_der(ski.extn_value)?
+            .0
+            .as_bytes()
+            .to_vec()),
+        None => Ok(SubjectPublicKeyInfo::as_key_identifier(
+            issuer.tbs_certificate.subject_public_key_info,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr};
+
+    use der::{asn1::ObjectIdentifier, Decode};
+    use x509_cert::ext::pkix::{
+        name, AuthorityInfoAccessSyntax, AuthorityKeyIdentifier, CrlDistributionPoints,
+        ExtendedKeyUsage, NameConstraints, PolicyMappings, SubjectAltName, SubjectInfoAccessSyntax,
+    };
+
+    use super::{
+        AuthorityInfoAccess, CertificatePolicy, Extensions, KeyPurpose, NoticeReference,
+        PolicyQualifier, SubjectInfoAccess, TlsFeature,
+    };
+    use crate::{
+        context::Context,
+        error::Error,
+        general_name::{GeneralName, GeneralSubtree},
+        name::Name,
+        testing::{certificate, tbs_certificate},
+    };
+
+    #[test]
+    fn test_subject_alt_name() {
+        let context = Context::new();
+        let subject = Name::from_pairs(&context, &[("CN", "example.com")]).unwrap();
+        let empty = Name::from_pairs::<&str, &str>(&context, &[]).unwrap();
+        let names = [
+            GeneralName::DnsName("example.com"),
+            GeneralName::IpAddress(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+        ];
+
+        let ext = Extensions::subject_alt_name(&context, &subject, &names).unwrap();
+        assert!(!ext.critical);
+
+        let san = SubjectAltName::from_der(ext.extn_value).unwrap();
+        assert!(
+            matches!(&san.0[1], name::GeneralName::IpAddress(ip) if ip.as_bytes() == [10, 0, 0, 1])
+        );
+
+        let ext = Extensions::subject_alt_name(&context, &empty, &names).unwrap();
+        assert!(ext.critical);
+
+        assert!(Extensions::subject_alt_name(&context, &subject, &[]).is_err());
+    }
+
+    #[test]
+    fn test_extended_key_usage() {
+        let context = Context::new();
+        let document_signing = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.10.3.12");
+        let purposes = [KeyPurpose::ServerAuth, KeyPurpose::Other(document_signing)];
+
+        let ext = Extensions::extended_key_usage(&context, false, &purposes).unwrap();
+        assert_eq!(ext.extn_id, const_oid::db::rfc5280::ID_CE_EXT_KEY_USAGE);
+
+        let eku = ExtendedKeyUsage::from_der(ext.extn_value).unwrap();
+        assert_eq!(
+            eku.0,
+            [const_oid::db::rfc5280::ID_KP_SERVER_AUTH, document_signing]
+        );
+        assert_eq!(
+            KeyPurpose::from(eku.0[1]),
+            KeyPurpose::Other(document_signing)
+        );
+
+        assert!(Extensions::extended_key_usage(&context, false, &[]).is_err());
+    }
+
+    #[test]
+    fn test_name_constraints() {
+        let context = Context::new();
+        let permitted = [
+            GeneralSubtree::DnsName("example.com"),
+            GeneralSubtree::IpNetwork(IpAddr::V4(Ipv4Addr::new(10, 1, 0, 0)), 16),
+        ];
+
+        let ext = Extensions::name_constraints(&context, true, &permitted, &[]).unwrap();
+        let constraints = NameConstraints::from_der(ext.extn_value).unwrap();
+
+        assert!(constraints.excluded_subtrees.is_none());
+        assert_eq!(constraints.permitted_subtrees.unwrap().len(), 2);
+
+        assert!(Extensions::name_constraints(&context, true, &[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_crl_distribution_points() {
+        let context = Context::new();
+        let names = [
+            GeneralName::Uri("http://crl.example.com/ca.crl"),
+            GeneralName::Uri("ldap://ldap.example.com/cn=ca"),
+        ];
+
+        let ext = Extensions::crl_distribution_points(&context, false, &names).unwrap();
+        assert_eq!(
+            ext.extn_id,
+            const_oid::db::rfc5280::ID_CE_CRL_DISTRIBUTION_POINTS
+        );
+
+        let points = CrlDistributionPoints::from_der(ext.extn_value).unwrap();
+        assert_eq!(points.0.len(), 1);
+        assert!(points.0[0].reasons.is_none());
+        assert!(points.0[0].crl_issuer.is_none());
+        assert!(matches!(
+            &points.0[0].distribution_point,
+            Some(name::DistributionPointName::FullName(names))
+                if matches!(&names[..], [
+                    name::GeneralName::UniformResourceIdentifier(a),
+                    name::GeneralName::UniformResourceIdentifier(b),
+                ] if a.as_str() == "http://crl.example.com/ca.crl"
+                    && b.as_str() == "ldap://ldap.example.com/cn=ca")
+        ));
+
+        assert!(Extensions::crl_distribution_points(&context, false, &[]).is_err());
+    }
+
+    #[test]
+    fn test_info_access() {
+        let context = Context::new();
+
+        let ext = Extensions::authority_info_access(
+            &context,
+            &[
+                AuthorityInfoAccess::Ocsp(GeneralName::Uri("http://ocsp.example.com")),
+                AuthorityInfoAccess::CaIssuers(GeneralName::Uri("http://example.com/ca.crt")),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            ext.extn_id,
+            const_oid::db::rfc5280::ID_PE_AUTHORITY_INFO_ACCESS
+        );
+        assert!(!ext.critical);
+
+        let aia = AuthorityInfoAccessSyntax::from_der(ext.extn_value).unwrap();
+        let methods: Vec<_> = aia.0.iter().map(|d| d.access_method).collect();
+        assert_eq!(
+            methods,
+            [
+                const_oid::db::rfc5280::ID_AD_OCSP,
+                const_oid::db::rfc5280::ID_AD_CA_ISSUERS
+            ]
+        );
+        assert!(matches!(
+            &aia.0[0].access_location,
+            name::GeneralName::UniformResourceIdentifier(uri) if uri.as_str() == "http://ocsp.example.com"
+        ));
+
+        let ext = Extensions::subject_info_access(
+            &context,
+            &[
+                SubjectInfoAccess::CaRepository(GeneralName::Uri("rsync://example.com/repo/")),
+                SubjectInfoAccess::TimeStamping(GeneralName::Uri("http://tsa.example.com")),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            ext.extn_id,
+            const_oid::db::rfc5280::ID_PE_SUBJECT_INFO_ACCESS
+        );
+        assert!(!ext.critical);
+
+        let sia = SubjectInfoAccessSyntax::from_der(ext.extn_value).unwrap();
+        let methods: Vec<_> = sia.0.iter().map(|d| d.access_method).collect();
+        assert_eq!(
+            methods,
+            [
+                const_oid::db::rfc5280::ID_AD_CA_REPOSITORY,
+                const_oid::db::rfc5280::ID_AD_TIME_STAMPING
+            ]
+        );
+
+        assert!(Extensions::authority_info_access(&context, &[]).is_err());
+        assert!(Extensions::subject_info_access(&context, &[]).is_err());
+    }
+
+    #[test]
+    fn test_certificate_policies() {
+        let long_text = "x".repeat(201);
+        let context = Context::new();
+        let mut policy = CertificatePolicy::new(const_oid::db::rfc5280::ANY_POLICY);
+        policy.qualifiers.push(PolicyQualifier::UserNotice {
+            notice_ref: Some(NoticeReference {
+                organization: "Acme",
+                notice_numbers: vec![1, 2],
+            }),
+            explicit_text: Some("Hi"),
+        });
+
+        let ext = Extensions::certificate_policies(&context, false, &[policy.clone()]).unwrap();
+
+        let expected = [
+            0x30, 0x2c, 0x30, 0x2a, 0x06, 0x04, 0x55, 0x1d, 0x20, 0x00, 0x30, 0x22, 0x30, 0x20,
+            0x06, 0x08, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x02, 0x02, 0x30, 0x14, 0x30, 0x0e,
+            0x0c, 0x04, b'A', b'c', b'm', b'e', 0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02,
+            0x0c, 0x02, b'H', b'i',
+        ];
+        assert_eq!(ext.extn_value, expected);
+
+        policy.qualifiers = vec![PolicyQualifier::UserNotice {
+            notice_ref: None,
+            explicit_text: Some(&long_text),
+        }];
+        assert!(Extensions::certificate_policies(&context, false, &[policy]).is_err());
+        assert!(Extensions::policy_constraints(&context, true, None, None).is_err());
+    }
+
+    #[test]
+    fn test_policy_mappings() {
+        let context = Context::new();
+        let issuer_policy = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.44947.1.1.1");
+        let subject_policy = ObjectIdentifier::new_unwrap("2.23.140.1.2.1");
+
+        let ext = Extensions::policy_mappings(&context, true, &[(issuer_policy, subject_policy)])
+            .unwrap();
+        assert_eq!(ext.extn_id, const_oid::db::rfc5280::ID_CE_POLICY_MAPPINGS);
+
+        let mappings = PolicyMappings::from_der(ext.extn_value).unwrap();
+        assert_eq!(mappings.0.len(), 1);
+        assert_eq!(mappings.0[0].issuer_domain_policy, issuer_policy);
+        assert_eq!(mappings.0[0].subject_domain_policy, subject_policy);
+
+        let any = const_oid::db::rfc5280::ANY_POLICY;
+        assert!(matches!(
+            Extensions::policy_mappings(&context, true, &[(any, subject_policy)]),
+            Err(Error::AnyPolicyMapping)
+        ));
+        assert!(matches!(
+            Extensions::policy_mappings(&context, true, &[(issuer_policy, any)]),
+            Err(Error::AnyPolicyMapping)
+        ));
+        assert!(Extensions::policy_mappings(&context, true, &[]).is_err());
+    }
+
+    #[test]
+    fn test_inhibit_any_policy() {
+        let context = Context::new();
+
+        let ext = Extensions::inhibit_any_policy(&context, true, 2).unwrap();
+        assert_eq!(
+            ext.extn_id,
+            const_oid::db::rfc5280::ID_CE_INHIBIT_ANY_POLICY
+        );
+        assert!(ext.critical);
+        assert_eq!(ext.extn_value, [0x02, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn test_authority_key_identifier_from_issuer() {
+        let context = Context::new();
+        let ski = [0x30, 0x03, 0x04, 0x01, 0x07];
+        let issuer = certificate(tbs_certificate(
+            &context,
+            Some(vec![x509_cert::ext::Extension {
+                extn_id: const_oid::db::rfc5280::ID_CE_SUBJECT_KEY_IDENTIFIER,
+                critical: false,
+                extn_value: &ski[2..],
+            }]),
+        ));
+
+        let ext =
+            Extensions::authority_key_identifier_from_issuer(&context, false, &issuer).unwrap();
+        let aki = AuthorityKeyIdentifier::from_der(ext.extn_value).unwrap();
+        assert_eq!(aki.key_identifier.unwrap().as_bytes(), [0x07]);
+        assert!(aki.authority_cert_issuer.is_none());
+
+        let ext = Extensions::authority_key_identifier_with_issuer_serial(&context, false, &issuer)
+            .unwrap();
+        let aki = AuthorityKeyIdentifier::from_der(ext.extn_value).unwrap();
+        assert_eq!(aki.authority_cert_serial_number.unwrap().as_bytes(), [0x42]);
+        assert!(matches!(
+            aki.authority_cert_issuer.as_deref(),
+            Some([name::GeneralName::DirectoryName(_)])
+        ));
+
+        let issuer = certificate(tbs_certificate(&context, None));
+        let ext =
+            Extensions::authority_key_identifier_from_issuer(&context, false, &issuer).unwrap();
+        let aki = AuthorityKeyIdentifier::from_der(ext.extn_value).unwrap();
+        assert_eq!(aki.key_identifier.unwrap().as_bytes().len(), 20);
+    }
+
+    #[test]
+    fn test_tls_feature() {
+        let context = Context::new();
+
+        let ext = Extensions::tls_feature(&context, false, &[TlsFeature::StatusRequest]).unwrap();
+        assert_eq!(ext.extn_value, [0x30, 0x03, 0x02, 0x01, 0x05]);
+
+        let ext = Extensions::ocsp_no_check(&context, false).unwrap();
+        assert_eq!(ext.extn_value, [0x05, 0x00]);
+
+        assert!(Extensions::tls_feature(&context, false, &[]).is_err());
+    }
 }
@@ -0,0 +1,539 @@
+use const_oid::ObjectIdentifier;
+use der::{asn1::Null, Decode};
+use x509_cert::{
+    ext::{self, pkix::KeyUsages},
+    Certificate,
+};
+
+use super::{
+    AuthorityInfoAccess, CertificatePolicy, KeyPurpose, QcStatement, SubjectInfoAccess, TlsFeature,
+    ID_PE_QC_STATEMENTS, ID_PE_TLS_FEATURE,
+};
+use crate::{
+    context::Context,
+    error::{Error, Result},
+    general_name::{GeneralName, GeneralSubtree},
+};
+
+/// A typed view of an extension read back from an existing certificate.
+///
+/// Extensions without a typed representation are kept as [`DecodedExtension::Unknown`], so
+/// they can be passed through unchanged. So are extensions using parts of their syntax the
+/// typed variant can't express, e.g. CRL distribution points with `reasons` or names containing
+/// an `ediPartyName`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodedExtension<'a> {
+    BasicConstraints {
+        ca: bool,
+        path_len_constraint: Option<u8>,
+    },
+    KeyUsage(Vec<KeyUsages>),
+    ExtendedKeyUsage(Vec<KeyPurpose>),
+    SubjectAltName(Vec<GeneralName<'a>>),
+    IssuerAltName(Vec<GeneralName<'a>>),
+    SubjectKeyIdentifier(&'a [u8]),
+    AuthorityKeyIdentifier {
+        key_identifier: Option<&'a [u8]>,
+        authority_cert_issuer: Vec<GeneralName<'a>>,
+        /// Big endian serial number of the issuer's certificate
+        authority_cert_serial_number: Option<&'a [u8]>,
+    },
+    NameConstraints {
+        permitted: Vec<GeneralSubtree<'a>>,
+        excluded: Vec<GeneralSubtree<'a>>,
+    },
+    /// The `fullName` of each distribution point
+    CrlDistributionPoints(Vec<Vec<GeneralName<'a>>>),
+    AuthorityInfoAccess(Vec<AuthorityInfoAccess<'a>>),
+    SubjectInfoAccess(Vec<SubjectInfoAccess<'a>>),
+    CertificatePolicies(Vec<CertificatePolicy<'a>>),
+    /// `(issuerDomainPolicy, subjectDomainPolicy)` pairs
+    PolicyMappings(Vec<(ObjectIdentifier, ObjectIdentifier)>),
+    PolicyConstraints {
+        require_explicit_policy: Option<u32>,
+        inhibit_policy_mapping: Option<u32>,
+    },
+    InhibitAnyPolicy(u32),
+    OcspNoCheck,
+    TlsFeature(Vec<TlsFeature>),
+    QcStatements(Vec<QcStatement<'a>>),
+    Unknown(ext::Extension<'a>),
+}
+
+impl<'a> DecodedExtension<'a> {
+    /// Decodes the `extnValue` of a known extension, failing when it is malformed
+    pub fn from_x509(context: &'a Context, extension: &ext::Extension<'a>) -> Result<Self> {
+        match Self::decode(context, extension) {
+            Err(Error::UnsupportedGeneralName) => Ok(DecodedExtension::Unknown(extension.clone())),
+            decoded => decoded,
+        }
+    }
+
+    fn decode(context: &'a Context, extension: &ext::Extension<'a>) -> Result<Self> {
+        let value = extension.extn_value;
+
+        Ok(match extension.extn_id {
+            const_oid::db::rfc5280::ID_CE_BASIC_CONSTRAINTS => {
+                let bc = ext::pkix::BasicConstraints::from_der(value)?;
+
+                DecodedExtension::BasicConstraints {
+                    ca: bc.ca,
+                    path_len_constraint: bc.path_len_constraint,
+                }
+            }
+            const_oid::db::rfc5280::ID_CE_KEY_USAGE => {
+                let usages = ext::pkix::KeyUsage::from_der(value)?;
+
+                DecodedExtension::KeyUsage(usages.0.into_iter().collect())
+            }
+            const_oid::db::rfc5280::ID_CE_EXT_KEY_USAGE => {
+                let purposes = ext::pkix::ExtendedKeyUsage::from_der(value)?;
+
+                DecodedExtension::ExtendedKeyUsage(purposes.0.into_iter().map(Into::into).collect())
+            }
+            const_oid::db::rfc5280::ID_CE_SUBJECT_ALT_NAME => {
+                let names = ext::pkix::SubjectAltName::from_der(value)?;
+
+                DecodedExtension::SubjectAltName(GeneralName::from_x509_names(context, &names.0)?)
+            }
+            const_oid::db::rfc5280::ID_CE_ISSUER_ALT_NAME => {
+                let names = ext::pkix::IssuerAltName::from_der(value)?;
+
+                DecodedExtension::IssuerAltName(GeneralName::from_x509_names(context, &names.0)?)
+            }
+            const_oid::db::rfc5280::ID_CE_SUBJECT_KEY_IDENTIFIER => {
+                let ski = ext::pkix::SubjectKeyIdentifier::from_der(value)?;
+
+                DecodedExtension::SubjectKeyIdentifier(ski.0.as_bytes())
+            }
+            const_oid::db::rfc5280::ID_CE_AUTHORITY_KEY_IDENTIFIER => {
+                let aki = ext::pkix::AuthorityKeyIdentifier::from_der(value)?;
+
+                DecodedExtension::AuthorityKeyIdentifier {
+                    key_identifier: aki.key_identifier.map(|k| k.as_bytes()),
+                    authority_cert_issuer: match aki.authority_cert_issuer {
+                        Some(ref names) => GeneralName::from_x509_names(context, names)?,
+                        None => Vec::new(),
+                    },
+                    authority_cert_serial_number: aki
+                        .authority_cert_serial_number
+                        .map(|s| s.as_bytes()),
+                }
+            }
+            const_oid::db::rfc5280::ID_CE_NAME_CONSTRAINTS => {
+                let nc = ext::pkix::NameConstraints::from_der(value)?;
+                let subtrees = |subtrees: &Option<Vec<_>>| {
+                    subtrees
+                        .iter()
+                        .flatten()
+                        .map(GeneralSubtree::from_x509)
+                        .collect::<Result<Vec<_>>>()
+                };
+
+                match (
+                    subtrees(&nc.permitted_subtrees),
+                    subtrees(&nc.excluded_subtrees),
+                ) {
+                    (Ok(permitted), Ok(excluded)) => DecodedExtension::NameConstraints {
+                        permitted,
+                        excluded,
+                    },
+                    _ => DecodedExtension::Unknown(extension.clone()),
+                }
+            }
+            const_oid::db::rfc5280::ID_CE_CRL_DISTRIBUTION_POINTS => {
+                let points = ext::pkix::CrlDistributionPoints::from_der(value)?;
+                let full_names = points
+                    .0
+                    .iter()
+                    .map(|point| match point {
+                        ext::pkix::crl::dp::DistributionPoint {
+                            distribution_point:
+                                Some(ext::pkix::name::DistributionPointName::FullName(names)),
+                            reasons: None,
+                            crl_issuer: None,
+                        } => Some(names),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>();
+
+                match full_names {
+                    Some(full_names) => DecodedExtension::CrlDistributionPoints(
+                        full_names
+                            .into_iter()
+                            .map(|names| GeneralName::from_x509_names(context, names))
+                            .collect::<Result<_>>()?,
+                    ),
+                    None => DecodedExtension::Unknown(extension.clone()),
+                }
+            }
+            const_oid::db::rfc5280::ID_PE_AUTHORITY_INFO_ACCESS => {
+                ext::pkix::AuthorityInfoAccessSyntax::from_der(value)?
+                    .0
+                    .iter()
+                    .map(|d| AuthorityInfoAccess::from_x509(context, d))
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .collect::<Option<_>>()
+                    .map_or_else(
+                        || DecodedExtension::Unknown(extension.clone()),
+                        DecodedExtension::AuthorityInfoAccess,
+                    )
+            }
+            const_oid::db::rfc5280::ID_PE_SUBJECT_INFO_ACCESS => {
+                ext::pkix::SubjectInfoAccessSyntax::from_der(value)?
+                    .0
+                    .iter()
+                    .map(|d| SubjectInfoAccess::from_x509(context, d))
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .collect::<Option<_>>()
+                    .map_or_else(
+                        || DecodedExtension::Unknown(extension.clone()),
+                        DecodedExtension::SubjectInfoAccess,
+                    )
+            }
+            const_oid::db::rfc5280::ID_CE_CERTIFICATE_POLICIES => {
+                ext::pkix::CertificatePolicies::from_der(value)?
+                    .0
+                    .iter()
+                    .map(|p| CertificatePolicy::from_x509(context, p))
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .collect::<Option<_>>()
+                    .map_or_else(
+                        || DecodedExtension::Unknown(extension.clone()),
+                        DecodedExtension::CertificatePolicies,
+                    )
+            }
+            const_oid::db::rfc5280::ID_CE_POLICY_MAPPINGS => DecodedExtension::PolicyMappings(
+                ext::pkix::PolicyMappings::from_der(value)?
+                    .0
+                    .into_iter()
+                    .map(|m| (m.issuer_domain_policy, m.subject_domain_policy))
+                    .collect(),
+            ),
+            const_oid::db::rfc5280::ID_CE_POLICY_CONSTRAINTS => {
+                let pc = ext::pkix::PolicyConstraints::from_der(value)?;
+
+                DecodedExtension::PolicyConstraints {
+                    require_explicit_policy: pc.require_explicit_policy,
+                    inhibit_policy_mapping: pc.inhibit_policy_mapping,
+                }
+            }
+            const_oid::db::rfc5280::ID_CE_INHIBIT_ANY_POLICY => {
+                DecodedExtension::InhibitAnyPolicy(ext::pkix::InhibitAnyPolicy::from_der(value)?.0)
+            }
+            const_oid::db::rfc6960::ID_PKIX_OCSP_NOCHECK => {
+                Null::from_der(value)?;
+
+                DecodedExtension::OcspNoCheck
+            }
+            ID_PE_TLS_FEATURE => {
+                let types = Vec::<u16>::from_der(value)?;
+
+                DecodedExtension::TlsFeature(types.into_iter().map(Into::into).collect())
+            }
+            ID_PE_QC_STATEMENTS => {
+                DecodedExtension::QcStatements(QcStatement::decode_all(context, value)?)
+            }
+            _ => DecodedExtension::Unknown(extension.clone()),
+        })
+    }
+
+    /// Decodes all extensions of `certificate`, in the order they appear
+    pub fn from_certificate(
+        context: &'a Context,
+        certificate: &Certificate<'a>,
+    ) -> Result<Vec<Self>> {
+        certificate
+            .tbs_certificate
+            .extensions
+            .iter()
+            .flatten()
+            .map(|e| Self::from_x509(context, e))
+            .collect()
+    }
+
+    pub fn oid(&self) -> ObjectIdentifier {
+        match self {
+            DecodedExtension::BasicConstraints { .. } => {
+                const_oid::db::rfc5280::ID_CE_BASIC_CONSTRAINTS
+            }
+            DecodedExtension::KeyUsage(_) => const_oid::db::rfc5280::ID_CE_KEY_USAGE,
+            DecodedExtension::ExtendedKeyUsage(_) => const_oid::db::rfc5280::ID_CE_EXT_KEY_USAGE,
+            DecodedExtension::SubjectAltName(_) => const_oid::db::rfc5280::ID_CE_SUBJECT_ALT_NAME,
+            DecodedExtension::IssuerAltName(_) => const_oid::db::rfc5280::ID_CE_ISSUER_ALT_NAME,
+            DecodedExtension::SubjectKeyIdentifier(_) => {
+                const_oid::db::rfc5280::ID_CE_SUBJECT_KEY_IDENTIFIER
+            }
+            DecodedExtension::AuthorityKeyIdentifier { .. } => {
+                const_oid::db::rfc5280::ID_CE_AUTHORITY_KEY_IDENTIFIER
+            }
+            DecodedExtension::NameConstraints { .. } => {
+                const_oid::db::rfc5280::ID_CE_NAME_CONSTRAINTS
+            }
+            DecodedExtension::CrlDistributionPoints(_) => {
+                const_oid::db::rfc5280::ID_CE_CRL_DISTRIBUTION_POINTS
+            }
+            DecodedExtension::AuthorityInfoAccess(_) => {
+                const_oid::db::rfc5280::ID_PE_AUTHORITY_INFO_ACCESS
+            }
+            DecodedExtension::SubjectInfoAccess(_) => {
+                const_oid::db::rfc5280::ID_PE_SUBJECT_INFO_ACCESS
+            }
+            DecodedExtension::CertificatePolicies(_) => {
+                const_oid::db::rfc5280::ID_CE_CERTIFICATE_POLICIES
+            }
+            DecodedExtension::PolicyMappings(_) => const_oid::db::rfc5280::ID_CE_POLICY_MAPPINGS,
+            DecodedExtension::PolicyConstraints { .. } => {
+                const_oid::db::rfc5280::ID_CE_POLICY_CONSTRAINTS
+            }
+            DecodedExtension::InhibitAnyPolicy(_) => {
+                const_oid::db::rfc5280::ID_CE_INHIBIT_ANY_POLICY
+            }
+            DecodedExtension::OcspNoCheck => const_oid::db::rfc6960::ID_PKIX_OCSP_NOCHECK,
+            DecodedExtension::TlsFeature(_) => ID_PE_TLS_FEATURE,
+            DecodedExtension::QcStatements(_) => ID_PE_QC_STATEMENTS,
+            DecodedExtension::Unknown(extension) => extension.extn_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use der::Encode;
+    use x509_cert::ext::pkix::{
+        crl::dp::{DistributionPoint, Reasons},
+        name::DistributionPointName,
+        CrlDistributionPoints, KeyUsages,
+    };
+
+    use super::DecodedExtension;
+    use crate::{
+        context::Context,
+        extensions::{
+            AuthorityInfoAccess, CertificatePolicy, Extensions, KeyPurpose, NoticeReference,
+            PolicyQualifier, SubjectInfoAccess, TlsFeature,
+        },
+        general_name::{GeneralName, GeneralSubtree},
+        name::Name,
+    };
+
+    #[test]
+    fn test_decode_roundtrip() {
+        let context = Context::new();
+        let subject = Name::from_pairs(&context, &[("CN", "example.com")]).unwrap();
+
+        let decode = |e| DecodedExtension::from_x509(&context, &e).unwrap();
+
+        assert_eq!(
+            decode(Extensions::basic_constraints(&context, true, true, Some(1)).unwrap()),
+            DecodedExtension::BasicConstraints {
+                ca: true,
+                path_len_constraint: Some(1)
+            }
+        );
+        assert_eq!(
+            decode(
+                Extensions::key_usage(
+                    &context,
+                    true,
+                    &[KeyUsages::DigitalSignature, KeyUsages::KeyCertSign]
+                )
+                .unwrap()
+            ),
+            DecodedExtension::KeyUsage(vec![KeyUsages::DigitalSignature, KeyUsages::KeyCertSign])
+        );
+        assert_eq!(
+            decode(
+                Extensions::extended_key_usage(&context, false, &[KeyPurpose::ServerAuth]).unwrap()
+            ),
+            DecodedExtension::ExtendedKeyUsage(vec![KeyPurpose::ServerAuth])
+        );
+
+        let names = [
+            GeneralName::DnsName("example.com"),
+            GeneralName::IpAddress(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+            GeneralName::OtherName {
+                type_id: const_oid::db::rfc5280::ID_CE_KEY_USAGE,
+                value: &[0x0c, 0x01, b'a'],
+            },
+        ];
+        assert_eq!(
+            decode(Extensions::subject_alt_name(&context, &subject, &names).unwrap()),
+            DecodedExtension::SubjectAltName(names.to_vec())
+        );
+        assert_eq!(
+            decode(Extensions::tls_feature(&context, false, &[TlsFeature::StatusRequest]).unwrap()),
+            DecodedExtension::TlsFeature(vec![TlsFeature::StatusRequest])
+        );
+        assert_eq!(
+            decode(Extensions::ocsp_no_check(&context, false).unwrap()),
+            DecodedExtension::OcspNoCheck
+        );
+
+        let permitted = [
+            GeneralSubtree::DnsName("example.com"),
+            GeneralSubtree::IpNetwork(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 0)), 8),
+        ];
+        let excluded = [GeneralSubtree::DirectoryName(subject.clone())];
+        assert_eq!(
+            decode(Extensions::name_constraints(&context, true, &permitted, &excluded).unwrap()),
+            DecodedExtension::NameConstraints {
+                permitted: permitted.to_vec(),
+                excluded: excluded.to_vec()
+            }
+        );
+
+        let crl = [GeneralName::Uri("http://crl.example.com/ca.crl")];
+        assert_eq!(
+            decode(Extensions::crl_distribution_points(&context, false, &crl).unwrap()),
+            DecodedExtension::CrlDistributionPoints(vec![crl.to_vec()])
+        );
+
+        let aia = [
+            AuthorityInfoAccess::Ocsp(GeneralName::Uri("http://ocsp.example.com")),
+            AuthorityInfoAccess::CaIssuers(GeneralName::Uri("http://example.com/ca.crt")),
+        ];
+        assert_eq!(
+            decode(Extensions::authority_info_access(&context, &aia).unwrap()),
+            DecodedExtension::AuthorityInfoAccess(aia.to_vec())
+        );
+
+        let sia = [SubjectInfoAccess::CaRepository(GeneralName::Uri(
+            "http://example.com/certs/",
+        ))];
+        assert_eq!(
+            decode(Extensions::subject_info_access(&context, &sia).unwrap()),
+            DecodedExtension::SubjectInfoAccess(sia.to_vec())
+        );
+
+        let policies = [
+            CertificatePolicy::new(const_oid::db::rfc5280::ANY_POLICY),
+            CertificatePolicy {
+                policy: const_oid::ObjectIdentifier::new_unwrap("1.2.3.4"),
+                qualifiers: vec![
+                    PolicyQualifier::CpsUri("https://example.com/cps"),
+                    PolicyQualifier::UserNotice {
+                        notice_ref: Some(NoticeReference {
+                            organization: "Example",
+                            notice_numbers: vec![1, 2],
+                        }),
+                        explicit_text: Some("Für Tests"),
+                    },
+                ],
+            },
+        ];
+        assert_eq!(
+            decode(Extensions::certificate_policies(&context, false, &policies).unwrap()),
+            DecodedExtension::CertificatePolicies(policies.to_vec())
+        );
+
+        // DisplayText written as VisibleString by other tools
+        let visible_notice = Extensions::raw(
+            &context,
+            const_oid::db::rfc5280::ID_CE_CERTIFICATE_POLICIES,
+            false,
+            &[
+                0x30, 0x1b, 0x30, 0x19, 0x06, 0x03, 0x2a, 0x03, 0x04, 0x30, 0x12, 0x30, 0x10, 0x06,
+                0x08, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x02, 0x02, 0x30, 0x04, 0x1a, 0x02, 0x48,
+                0x69,
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            decode(visible_notice),
+            DecodedExtension::CertificatePolicies(vec![CertificatePolicy {
+                policy: const_oid::ObjectIdentifier::new_unwrap("1.2.3.4"),
+                qualifiers: vec![PolicyQualifier::UserNotice {
+                    notice_ref: None,
+                    explicit_text: Some("Hi"),
+                }],
+            }])
+        );
+
+        // DisplayText written as BMPString, "Hi" in big endian UTF-16
+        let bmp_notice = Extensions::raw(
+            &context,
+            const_oid::db::rfc5280::ID_CE_CERTIFICATE_POLICIES,
+            false,
+            &[
+                0x30, 0x1d, 0x30, 0x1b, 0x06, 0x03, 0x2a, 0x03, 0x04, 0x30, 0x14, 0x30, 0x12, 0x06,
+                0x08, 0x2b, 0x06, 0x01, 0x05, 0x05, 0x07, 0x02, 0x02, 0x30, 0x06, 0x1e, 0x04, 0x00,
+                0x48, 0x00, 0x69,
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            decode(bmp_notice),
+            DecodedExtension::CertificatePolicies(vec![CertificatePolicy {
+                policy: const_oid::ObjectIdentifier::new_unwrap("1.2.3.4"),
+                qualifiers: vec![PolicyQualifier::UserNotice {
+                    notice_ref: None,
+                    explicit_text: Some("Hi"),
+                }],
+            }])
+        );
+
+        let mappings = [(
+            const_oid::ObjectIdentifier::new_unwrap("1.2.3.4"),
+            const_oid::ObjectIdentifier::new_unwrap("1.2.3.5"),
+        )];
+        assert_eq!(
+            decode(Extensions::policy_mappings(&context, true, &mappings).unwrap()),
+            DecodedExtension::PolicyMappings(mappings.to_vec())
+        );
+
+        // Distribution points with reasons can't be represented and are kept as they are
+        let uri = GeneralName::Uri("http://crl.example.com/ca.crl")
+            .to_x509(&context)
+            .unwrap();
+        let with_reasons = CrlDistributionPoints(vec![DistributionPoint {
+            distribution_point: Some(DistributionPointName::FullName(vec![uri])),
+            reasons: Some(Reasons::KeyCompromise.into()),
+            crl_issuer: None,
+        }])
+        .to_vec()
+        .unwrap();
+        let with_reasons = Extensions::raw(
+            &context,
+            const_oid::db::rfc5280::ID_CE_CRL_DISTRIBUTION_POINTS,
+            false,
+            &with_reasons,
+        )
+        .unwrap();
+        assert_eq!(
+            decode(with_reasons.clone()),
+            DecodedExtension::Unknown(with_reasons)
+        );
+
+        let unknown = Extensions::raw(
+            &context,
+            const_oid::db::rfc5280::ID_CE_FRESHEST_CRL,
+            false,
+            &[0x30, 0x00],
+        )
+        .unwrap();
+        assert_eq!(decode(unknown.clone()), DecodedExtension::Unknown(unknown));
+
+        // ediPartyName [5] { partyName [1] UTF8String "a" }
+        let edi_party = Extensions::raw(
+            &context,
+            const_oid::db::rfc5280::ID_CE_SUBJECT_ALT_NAME,
+            false,
+            &[0x30, 0x07, 0xa5, 0x05, 0xa1, 0x03, 0x0c, 0x01, b'a'],
+        )
+        .unwrap();
+        assert_eq!(
+            decode(edi_party.clone()),
+            DecodedExtension::Unknown(edi_party)
+        );
+
+        let mut broken = Extensions::inhibit_any_policy(&context, false, 1).unwrap();
+        broken.extn_value = &[0x04, 0x00];
+        assert!(DecodedExtension::from_x509(&context, &broken).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use const_oid::ObjectIdentifier;
+use der::{
+    asn1::{OctetStringRef, Utf8StringRef},
+    Encode, Sequence,
+};
+use x509_cert::ext;
+
+use super::Extensions;
+use crate::{
+    context::{Alloc, Context},
+    error::{Error, Result},
+    general_name::GeneralName,
+};
+
+/// `szOID_CERTIFICATE_TEMPLATE`, the version 2 certificate template extension
+pub const SZOID_CERTIFICATE_TEMPLATE: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.21.7");
+/// `szOID_NTDS_CA_SECURITY_EXT`, carries the SID of the account the certificate was issued to
+pub const SZOID_NTDS_CA_SECURITY_EXT: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.25.2");
+/// `szOID_NTDS_OBJECTSID`, the otherName type of the SID inside `szOID_NTDS_CA_SECURITY_EXT`
+pub const SZOID_NTDS_OBJECTSID: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.25.2.1");
+/// `szOID_NT_PRINCIPAL_NAME`, the otherName type of a user principal name
+pub const SZOID_NT_PRINCIPAL_NAME: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.20.2.3");
+
+/// The version 2 certificate template extension, identifying the Active Directory template
+/// a certificate was issued from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Sequence)]
+pub struct CertificateTemplate {
+    pub template_id: ObjectIdentifier,
+    pub major_version: u32,
+    pub minor_version: Option<u32>,
+}
+
+impl Extensions {
+    pub fn certificate_template<'a>(
+        context: &'a Context,
+        critical: bool,
+        template: &CertificateTemplate,
+    ) -> Result<ext::Extension<'a>> {
+        Ok(ext::Extension {
+            extn_id: SZOID_CERTIFICATE_TEMPLATE,
+            critical,
+            extn_value: template.to_vec()?.alloc_into(context),
+        })
+    }
+
+    /// Builds the SID extension used for strong certificate mapping, see
+    /// [KB5014754](https://support.microsoft.com/help/5014754). `sid` is the string form of
+    /// the account SID, e.g. `S-1-5-21-1004336348-1177238915-682003330-512`.
+    pub fn ntds_ca_security<'a>(
+        context: &'a Context,
+        critical: bool,
+        sid: &str,
+    ) -> Result<ext::Extension<'a>> {
+        if !is_valid_sid(sid) {
+            return Err(Error::InvalidSid(sid.to_string()));
+        }
+
+        let sid = GeneralName::OtherName {
+            type_id: SZOID_NTDS_OBJECTSID,
+            value: OctetStringRef::new(sid.as_bytes())?
+                .to_vec()?
+                .alloc_into(context),
+        };
+
+        let bytes = GeneralName::to_x509_names(context, &[sid])?.to_vec()?;
+
+        Ok(ext::Extension {
+            extn_id: SZOID_NTDS_CA_SECURITY_EXT,
+            critical,
+            extn_value: bytes.alloc_into(context),
+        })
+    }
+}
+
+impl<'a> GeneralName<'a> {
+    /// A user principal name otherName, as used for smart card logon
+    pub fn user_principal_name(context: &'a Context, upn: &str) -> Result<Self> {
+        Ok(GeneralName::OtherName {
+            type_id: SZOID_NT_PRINCIPAL_NAME,
+            value: Utf8StringRef::new(upn)?.to_vec()?.alloc_into(context),
+        })
+    }
+}
+
+/// Checks for the `S-1-<authority>-<subauthority>...` string form of a SID
+fn is_valid_sid(sid: &str) -> bool {
+    let Some(rest) = sid.strip_prefix("S-1-") else {
+        return false;
+    };
+
+    let parts: Vec<_> = rest.split('-').collect();
+
+    // An identifier authority followed by at most 15 subauthorities
+    parts.len() <= 16
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()))
+        && parts[1..].iter().all(|p| p.parse::<u32>().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_valid_sid, CertificateTemplate};
+    use crate::{
+        context::Context,
+        extensions::{DecodedExtension, Extensions},
+        general_name::GeneralName,
+    };
+
+    #[test]
+    fn test_ntds_ca_security() {
+        let context = Context::new();
+
+        let ext = Extensions::ntds_ca_security(&context, false, "S-1-5-21-1-2-3-500").unwrap();
+        assert_eq!(
+            ext.extn_value[..17],
+            [
+                0x30, 0x24, 0xa0, 0x22, 0x06, 0x0a, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x19,
+                0x02, 0x01, 0xa0
+            ]
+        );
+        assert!(ext
+            .extn_value
+            .ends_with(b"\xa0\x14\x04\x12S-1-5-21-1-2-3-500"));
+
+        assert!(is_valid_sid("S-1-5-18"));
+        assert!(!is_valid_sid("S-1-5-"));
+        assert!(!is_valid_sid("S-2-5-18"));
+        assert!(Extensions::ntds_ca_security(&context, false, "Administrator").is_err());
+    }
+
+    #[test]
+    fn test_certificate_template() {
+        let context = Context::new();
+
+        let template = CertificateTemplate {
+            template_id: const_oid::ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.21.8.1.2"),
+            major_version: 100,
+            minor_version: Some(4),
+        };
+        let ext = Extensions::certificate_template(&context, false, &template).unwrap();
+        assert_eq!(
+            ext.extn_value,
+            [
+                0x30, 0x13, 0x06, 0x0b, 0x2b, 0x06, 0x01, 0x04, 0x01, 0x82, 0x37, 0x15, 0x08, 0x01,
+                0x02, 0x02, 0x01, 0x64, 0x02, 0x01, 0x04
+            ]
+        );
+
+        let upn = GeneralName::user_principal_name(&context, "user@example.com").unwrap();
+        let san = Extensions::subject_alt_name(
+            &context,
+            &crate::name::Name::from_pairs(&context, &[("CN", "user")]).unwrap(),
+            std::slice::from_ref(&upn),
+        )
+        .unwrap();
+        assert_eq!(
+            DecodedExtension::from_x509(&context, &san).unwrap(),
+            DecodedExtension::SubjectAltName(vec![upn])
+        );
+    }
+}
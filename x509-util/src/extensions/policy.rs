@@ -0,0 +1,234 @@
+use const_oid::ObjectIdentifier;
+use der::{
+    asn1::{Ia5StringRef, Utf8StringRef},
+    AnyRef, Decode, Encode, ErrorKind, Length, Reader, Sequence, SliceReader, Tag, Tagged,
+};
+use x509_cert::ext::pkix::certpolicy;
+
+use crate::{
+    context::{Alloc, Context},
+    error::{Error, Result},
+};
+
+/// Upper bound of `explicitText` and `organization`, see
+/// [RFC 5280 Section 4.2.1.4](https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.4)
+const UB_DISPLAY_TEXT: usize = 200;
+
+/// A single PolicyInformation entry of the CertificatePolicies extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificatePolicy<'a> {
+    pub policy: ObjectIdentifier,
+    pub qualifiers: Vec<PolicyQualifier<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyQualifier<'a> {
+    CpsUri(&'a str),
+    UserNotice {
+        notice_ref: Option<NoticeReference<'a>>,
+        explicit_text: Option<&'a str>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoticeReference<'a> {
+    pub organization: &'a str,
+    pub notice_numbers: Vec<u32>,
+}
+
+/// `x509_cert` models `noticeRef` as a GeneralizedTime, so `UserNotice` is encoded here instead.
+/// `DisplayText` is always encoded as UTF8String, as recommended by RFC 6818.
+#[derive(Sequence)]
+struct UserNoticeDer<'a> {
+    notice_ref: Option<NoticeReferenceDer<'a>>,
+    explicit_text: Option<Utf8StringRef<'a>>,
+}
+
+#[derive(Sequence)]
+struct NoticeReferenceDer<'a> {
+    organization: Utf8StringRef<'a>,
+    notice_numbers: Vec<u32>,
+}
+
+fn display_text(text: &str) -> Result<Utf8StringRef<'_>> {
+    let len = text.chars().count();
+    if len == 0 || len > UB_DISPLAY_TEXT {
+        return Err(Error::InvalidDisplayText(len));
+    }
+
+    Ok(Utf8StringRef::new(text)?)
+}
+
+/// Reads a `DisplayText` from its tag byte and value. BMPString is decoded as big endian UTF-16
+/// here, `der` uses the tag of UniversalString for it.
+fn decode_display_text<'a>(context: &'a Context, tag: u8, value: &'a [u8]) -> Result<&'a str> {
+    let text = match tag {
+        // UTF8String, IA5String, VisibleString
+        0x0c | 0x16 | 0x1a => return Ok(std::str::from_utf8(value).map_err(der::Error::from)?),
+        // BMPString
+        0x1e => {
+            let units = value.chunks_exact(2);
+            if !units.remainder().is_empty() {
+                return Err(Tag::BmpString.value_error().into());
+            }
+
+            let units: Vec<u16> = units
+                .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+                .collect();
+            String::from_utf16(&units).map_err(|_| Tag::BmpString.value_error())?
+        }
+        byte => return Err(der::Error::from(ErrorKind::TagUnknown { byte }).into()),
+    };
+
+    Ok(std::str::from_utf8(text.alloc_into(context)).map_err(der::Error::from)?)
+}
+
+/// Reads the tag byte and value of the next element, without interpreting the tag
+fn read_tlv<'a>(reader: &mut SliceReader<'a>) -> Result<(u8, &'a [u8])> {
+    let tag = reader.read_byte()?;
+    let len: Length = reader.decode()?;
+
+    Ok((tag, reader.read_slice(len)?))
+}
+
+/// Reads a `UserNotice` as found in existing certificates, where `DisplayText` may be any of its
+/// string types. It is walked by hand since `der` can't decode a BMPString.
+fn decode_user_notice<'a>(context: &'a Context, notice: AnyRef<'a>) -> Result<PolicyQualifier<'a>> {
+    if notice.tag() != Tag::Sequence {
+        return Err(notice.tag().unexpected_error(Some(Tag::Sequence)).into());
+    }
+
+    let mut reader = SliceReader::new(notice.value())?;
+
+    let notice_ref = if reader.peek_byte() == Some(0x30) {
+        let (_, value) = read_tlv(&mut reader)?;
+        let mut reference = SliceReader::new(value)?;
+        let (tag, organization) = read_tlv(&mut reference)?;
+        let notice_numbers = reference.decode()?;
+        reference.finish(())?;
+
+        Some(NoticeReference {
+            organization: decode_display_text(context, tag, organization)?,
+            notice_numbers,
+        })
+    } else {
+        None
+    };
+
+    let explicit_text = if reader.is_finished() {
+        None
+    } else {
+        let (tag, text) = read_tlv(&mut reader)?;
+        Some(decode_display_text(context, tag, text)?)
+    };
+    reader.finish(())?;
+
+    Ok(PolicyQualifier::UserNotice {
+        notice_ref,
+        explicit_text,
+    })
+}
+
+impl<'a> PolicyQualifier<'a> {
+    fn to_x509(&self, context: &'a Context) -> Result<certpolicy::PolicyQualifierInfo<'a>> {
+        let (policy_qualifier_id, bytes) = match self {
+            PolicyQualifier::CpsUri(uri) => (
+                const_oid::db::rfc5280::ID_QT_CPS,
+                Ia5StringRef::new(*uri)?.to_vec()?,
+            ),
+            PolicyQualifier::UserNotice {
+                notice_ref,
+                explicit_text,
+            } => {
+                let notice_ref = match notice_ref {
+                    Some(r) => Some(NoticeReferenceDer {
+                        organization: display_text(r.organization)?,
+                        notice_numbers: r.notice_numbers.clone(),
+                    }),
+                    None => None,
+                };
+                let explicit_text = explicit_text.map(display_text).transpose()?;
+
+                let notice = UserNoticeDer {
+                    notice_ref,
+                    explicit_text,
+                };
+
+                (const_oid::db::rfc5280::ID_QT_UNOTICE, notice.to_vec()?)
+            }
+        };
+
+        Ok(certpolicy::PolicyQualifierInfo {
+            policy_qualifier_id,
+            qualifier: Some(AnyRef::from_der(bytes.alloc_into(context))?),
+        })
+    }
+
+    /// The inverse of [`PolicyQualifier::to_x509`], `None` for unknown qualifier types
+    fn from_x509(
+        context: &'a Context,
+        info: &certpolicy::PolicyQualifierInfo<'a>,
+    ) -> Result<Option<Self>> {
+        Ok(match (info.policy_qualifier_id, info.qualifier) {
+            (const_oid::db::rfc5280::ID_QT_CPS, Some(qualifier)) => Some(PolicyQualifier::CpsUri(
+                qualifier.decode_into::<Ia5StringRef<'a>>()?.as_str(),
+            )),
+            (const_oid::db::rfc5280::ID_QT_UNOTICE, Some(qualifier)) => {
+                Some(decode_user_notice(context, qualifier)?)
+            }
+            _ => None,
+        })
+    }
+}
+
+impl<'a> CertificatePolicy<'a> {
+    pub fn new(policy: ObjectIdentifier) -> Self {
+        CertificatePolicy {
+            policy,
+            qualifiers: Vec::new(),
+        }
+    }
+
+    pub(crate) fn to_x509(
+        &self,
+        context: &'a Context,
+    ) -> Result<certpolicy::PolicyInformation<'a>> {
+        let policy_qualifiers = if self.qualifiers.is_empty() {
+            None
+        } else {
+            Some(
+                self.qualifiers
+                    .iter()
+                    .map(|q| q.to_x509(context))
+                    .collect::<Result<_>>()?,
+            )
+        };
+
+        Ok(certpolicy::PolicyInformation {
+            policy_identifier: self.policy,
+            policy_qualifiers,
+        })
+    }
+
+    /// The inverse of [`CertificatePolicy::to_x509`], `None` if any qualifier is of an unknown
+    /// type
+    pub(crate) fn from_x509(
+        context: &'a Context,
+        information: &certpolicy::PolicyInformation<'a>,
+    ) -> Result<Option<Self>> {
+        let qualifiers = information
+            .policy_qualifiers
+            .iter()
+            .flatten()
+            .map(|q| PolicyQualifier::from_x509(context, q))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(qualifiers
+            .into_iter()
+            .collect::<Option<_>>()
+            .map(|qualifiers| CertificatePolicy {
+                policy: information.policy_identifier,
+                qualifiers,
+            }))
+    }
+}
@@ -0,0 +1,391 @@
+use const_oid::ObjectIdentifier;
+use der::{
+    asn1::{Ia5StringRef, PrintableStringRef, Utf8StringRef},
+    AnyRef, Decode, Encode, Sequence,
+};
+use x509_cert::ext;
+
+use super::Extensions;
+use crate::{
+    context::{Alloc, Context},
+    error::{Error, Result},
+};
+
+/// RFC 3739 `id-pe-qcStatements`
+pub const ID_PE_QC_STATEMENTS: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.5.5.7.1.3");
+
+const ID_ETSI_QCS_QC_COMPLIANCE: ObjectIdentifier = ObjectIdentifier::new_unwrap("0.4.0.1862.1.1");
+const ID_ETSI_QCS_QC_RETENTION_PERIOD: ObjectIdentifier =
+    ObjectIdentifier::new_unwrap("0.4.0.1862.1.3");
+const ID_ETSI_QCS_QC_SSCD: ObjectIdentifier = ObjectIdentifier::new_unwrap("0.4.0.1862.1.4");
+const ID_ETSI_QCS_QC_PDS: ObjectIdentifier = ObjectIdentifier::new_unwrap("0.4.0.1862.1.5");
+const ID_ETSI_QCS_QC_TYPE: ObjectIdentifier = ObjectIdentifier::new_unwrap("0.4.0.1862.1.6");
+const ID_ETSI_PSD2_QC_STATEMENT: ObjectIdentifier = ObjectIdentifier::new_unwrap("0.4.0.19495.2");
+
+/// Upper bound of the PSD2 role, NCA name and NCA id strings
+const UB_PSD2_STRING: usize = 256;
+
+/// A single statement of the QCStatements extension, see
+/// [ETSI EN 319 412-5](https://www.etsi.org/deliver/etsi_en/319400_319499/31941205/) and
+/// [ETSI TS 119 495](https://www.etsi.org/deliver/etsi_ts/119400_119499/119495/) for PSD2
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QcStatement<'a> {
+    /// The certificate is an EU qualified certificate
+    Compliance,
+    /// The private key resides in a qualified signature creation device
+    Sscd,
+    /// Number of years the CA keeps registration information after expiry
+    RetentionPeriod(u32),
+    Type(Vec<QcType>),
+    /// Locations of the PKI disclosure statements
+    Pds(Vec<PdsLocation<'a>>),
+    Psd2 {
+        roles: Vec<Psd2Role>,
+        nca_name: &'a str,
+        nca_id: &'a str,
+    },
+    /// `info` is the DER encoding of the statementInfo, if any
+    Other {
+        id: ObjectIdentifier,
+        info: Option<&'a [u8]>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QcType {
+    ESign,
+    ESeal,
+    Web,
+    Other(ObjectIdentifier),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdsLocation<'a> {
+    pub url: &'a str,
+    /// ISO 639-1 language code, e.g. `en`
+    pub language: &'a str,
+}
+
+/// Roles of a payment service provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Psd2Role {
+    /// `PSP_AS`, account servicing
+    AccountServicing,
+    /// `PSP_PI`, payment initiation
+    PaymentInitiation,
+    /// `PSP_AI`, account information
+    AccountInformation,
+    /// `PSP_IC`, issuing of card-based payment instruments
+    IssuingCardBased,
+}
+
+impl QcType {
+    pub fn oid(&self) -> ObjectIdentifier {
+        match self {
+            QcType::ESign => ObjectIdentifier::new_unwrap("0.4.0.1862.1.6.1"),
+            QcType::ESeal => ObjectIdentifier::new_unwrap("0.4.0.1862.1.6.2"),
+            QcType::Web => ObjectIdentifier::new_unwrap("0.4.0.1862.1.6.3"),
+            QcType::Other(oid) => *oid,
+        }
+    }
+}
+
+impl From<ObjectIdentifier> for QcType {
+    fn from(oid: ObjectIdentifier) -> Self {
+        [QcType::ESign, QcType::ESeal, QcType::Web]
+            .into_iter()
+            .find(|t| t.oid() == oid)
+            .unwrap_or(QcType::Other(oid))
+    }
+}
+
+impl Psd2Role {
+    const ALL: [Psd2Role; 4] = [
+        Psd2Role::AccountServicing,
+        Psd2Role::PaymentInitiation,
+        Psd2Role::AccountInformation,
+        Psd2Role::IssuingCardBased,
+    ];
+
+    pub fn oid(&self) -> ObjectIdentifier {
+        match self {
+            Psd2Role::AccountServicing => ObjectIdentifier::new_unwrap("0.4.0.19495.1.1"),
+            Psd2Role::PaymentInitiation => ObjectIdentifier::new_unwrap("0.4.0.19495.1.2"),
+            Psd2Role::AccountInformation => ObjectIdentifier::new_unwrap("0.4.0.19495.1.3"),
+            Psd2Role::IssuingCardBased => ObjectIdentifier::new_unwrap("0.4.0.19495.1.4"),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Psd2Role::AccountServicing => "PSP_AS",
+            Psd2Role::PaymentInitiation => "PSP_PI",
+            Psd2Role::AccountInformation => "PSP_AI",
+            Psd2Role::IssuingCardBased => "PSP_IC",
+        }
+    }
+
+    pub fn from_oid(oid: ObjectIdentifier) -> Option<Self> {
+        Self::ALL.into_iter().find(|r| r.oid() == oid)
+    }
+}
+
+#[derive(Sequence)]
+struct QcStatementDer<'a> {
+    statement_id: ObjectIdentifier,
+    statement_info: Option<AnyRef<'a>>,
+}
+
+#[derive(Sequence)]
+struct PdsLocationDer<'a> {
+    url: Ia5StringRef<'a>,
+    language: PrintableStringRef<'a>,
+}
+
+#[derive(Sequence)]
+struct Psd2Der<'a> {
+    roles: Vec<RoleDer<'a>>,
+    nca_name: Utf8StringRef<'a>,
+    nca_id: Utf8StringRef<'a>,
+}
+
+#[derive(Sequence)]
+struct RoleDer<'a> {
+    role: ObjectIdentifier,
+    name: Utf8StringRef<'a>,
+}
+
+fn psd2_string(text: &str) -> Result<Utf8StringRef<'_>> {
+    let len = text.chars().count();
+    if len == 0 || len > UB_PSD2_STRING {
+        return Err(Error::InvalidPsd2String(len));
+    }
+
+    Ok(Utf8StringRef::new(text)?)
+}
+
+impl<'a> QcStatement<'a> {
+    pub fn id(&self) -> ObjectIdentifier {
+        match self {
+            QcStatement::Compliance => ID_ETSI_QCS_QC_COMPLIANCE,
+            QcStatement::Sscd => ID_ETSI_QCS_QC_SSCD,
+            QcStatement::RetentionPeriod(_) => ID_ETSI_QCS_QC_RETENTION_PERIOD,
+            QcStatement::Type(_) => ID_ETSI_QCS_QC_TYPE,
+            QcStatement::Pds(_) => ID_ETSI_QCS_QC_PDS,
+            QcStatement::Psd2 { .. } => ID_ETSI_PSD2_QC_STATEMENT,
+            QcStatement::Other { id, .. } => *id,
+        }
+    }
+
+    /// DER encoding of the statementInfo
+    fn info(&self) -> Result<Option<Vec<u8>>> {
+        Ok(match self {
+            QcStatement::Compliance | QcStatement::Sscd => None,
+            QcStatement::RetentionPeriod(years) => Some(years.to_vec()?),
+            QcStatement::Type(types) => {
+                let oids: Vec<_> = types.iter().map(QcType::oid).collect();
+
+                Some(oids.to_vec()?)
+            }
+            QcStatement::Pds(locations) => {
+                let locations = locations
+                    .iter()
+                    .map(|l| {
+                        if l.language.len() != 2
+                            || !l.language.bytes().all(|b| b.is_ascii_alphabetic())
+                        {
+                            return Err(Error::InvalidPdsLanguage(l.language.to_string()));
+                        }
+
+                        Ok(PdsLocationDer {
+                            url: Ia5StringRef::new(l.url)?,
+                            language: PrintableStringRef::new(l.language)?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                Some(locations.to_vec()?)
+            }
+            QcStatement::Psd2 {
+                roles,
+                nca_name,
+                nca_id,
+            } => {
+                let roles = roles
+                    .iter()
+                    .map(|r| {
+                        Ok(RoleDer {
+                            role: r.oid(),
+                            name: Utf8StringRef::new(r.name())?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let psd2 = Psd2Der {
+                    roles,
+                    nca_name: psd2_string(nca_name)?,
+                    nca_id: psd2_string(nca_id)?,
+                };
+
+                Some(psd2.to_vec()?)
+            }
+            QcStatement::Other { info, .. } => info.map(<[u8]>::to_vec),
+        })
+    }
+
+    fn from_der(context: &'a Context, statement: QcStatementDer<'a>) -> Result<Self> {
+        Ok(match (statement.statement_id, statement.statement_info) {
+            (ID_ETSI_QCS_QC_COMPLIANCE, None) => QcStatement::Compliance,
+            (ID_ETSI_QCS_QC_SSCD, None) => QcStatement::Sscd,
+            (ID_ETSI_QCS_QC_RETENTION_PERIOD, Some(info)) => {
+                QcStatement::RetentionPeriod(info.decode_into()?)
+            }
+            (ID_ETSI_QCS_QC_TYPE, Some(info)) => {
+                let oids: Vec<ObjectIdentifier> = info.decode_into()?;
+
+                QcStatement::Type(oids.into_iter().map(Into::into).collect())
+            }
+            (ID_ETSI_QCS_QC_PDS, Some(info)) => {
+                let locations: Vec<PdsLocationDer<'a>> = info.decode_into()?;
+
+                QcStatement::Pds(
+                    locations
+                        .into_iter()
+                        .map(|l| PdsLocation {
+                            url: l.url.as_str(),
+                            language: l.language.as_str(),
+                        })
+                        .collect(),
+                )
+            }
+            (ID_ETSI_PSD2_QC_STATEMENT, Some(info)) => {
+                let psd2: Psd2Der<'a> = info.decode_into()?;
+
+                QcStatement::Psd2 {
+                    roles: psd2
+                        .roles
+                        .iter()
+                        .map(|r| Psd2Role::from_oid(r.role).ok_or(Error::InvalidQcStatement))
+                        .collect::<Result<_>>()?,
+                    nca_name: psd2.nca_name.as_str(),
+                    nca_id: psd2.nca_id.as_str(),
+                }
+            }
+            (
+                ID_ETSI_QCS_QC_COMPLIANCE
+                | ID_ETSI_QCS_QC_SSCD
+                | ID_ETSI_QCS_QC_RETENTION_PERIOD
+                | ID_ETSI_QCS_QC_TYPE
+                | ID_ETSI_QCS_QC_PDS
+                | ID_ETSI_PSD2_QC_STATEMENT,
+                _,
+            ) => return Err(Error::InvalidQcStatement),
+            (id, info) => QcStatement::Other {
+                id,
+                info: match info {
+                    Some(info) => Some(info.to_vec()?.alloc_into(context)),
+                    None => None,
+                },
+            },
+        })
+    }
+
+    /// Decodes the `extnValue` of a QCStatements extension
+    pub fn decode_all(context: &'a Context, der_value: &'a [u8]) -> Result<Vec<Self>> {
+        Vec::<QcStatementDer<'a>>::from_der(der_value)?
+            .into_iter()
+            .map(|s| Self::from_der(context, s))
+            .collect()
+    }
+}
+
+impl Extensions {
+    pub fn qc_statements<'a>(
+        context: &'a Context,
+        critical: bool,
+        statements: &[QcStatement<'_>],
+    ) -> Result<ext::Extension<'a>> {
+        if statements.is_empty() {
+            return Err(Error::EmptyQcStatements);
+        }
+
+        let infos = statements
+            .iter()
+            .map(QcStatement::info)
+            .collect::<Result<Vec<_>>>()?;
+
+        let statements = statements
+            .iter()
+            .zip(infos.iter())
+            .map(|(statement, info)| {
+                Ok(QcStatementDer {
+                    statement_id: statement.id(),
+                    statement_info: info.as_deref().map(AnyRef::from_der).transpose()?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ext::Extension {
+            extn_id: ID_PE_QC_STATEMENTS,
+            critical,
+            extn_value: statements.to_vec()?.alloc_into(context),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PdsLocation, Psd2Role, QcStatement, QcType};
+    use crate::{
+        context::Context,
+        extensions::{DecodedExtension, Extensions},
+    };
+
+    #[test]
+    fn test_qc_statements() {
+        let context = Context::new();
+
+        let statements = vec![
+            QcStatement::Compliance,
+            QcStatement::Sscd,
+            QcStatement::RetentionPeriod(15),
+            QcStatement::Type(vec![QcType::ESeal]),
+            QcStatement::Pds(vec![PdsLocation {
+                url: "https://example.com/pds_en.pdf",
+                language: "en",
+            }]),
+            QcStatement::Psd2 {
+                roles: vec![Psd2Role::PaymentInitiation, Psd2Role::AccountInformation],
+                nca_name: "Financial Conduct Authority",
+                nca_id: "GB-FCA",
+            },
+            QcStatement::Other {
+                id: const_oid::ObjectIdentifier::new_unwrap("1.2.3.4"),
+                info: Some(&[0x05, 0x00]),
+            },
+        ];
+
+        let ext = Extensions::qc_statements(&context, false, &statements).unwrap();
+
+        // QcCompliance and QcSSCD have no statementInfo
+        assert_eq!(
+            ext.extn_value[3..20],
+            [
+                0x30, 0x08, 0x06, 0x06, 0x04, 0x00, 0x8e, 0x46, 0x01, 0x01, 0x30, 0x08, 0x06, 0x06,
+                0x04, 0x00, 0x8e
+            ]
+        );
+
+        assert_eq!(
+            DecodedExtension::from_x509(&context, &ext).unwrap(),
+            DecodedExtension::QcStatements(statements)
+        );
+
+        let pds = QcStatement::Pds(vec![PdsLocation {
+            url: "https://example.com/pds.pdf",
+            language: "eng",
+        }]);
+        assert!(Extensions::qc_statements(&context, false, &[pds]).is_err());
+        assert!(Extensions::qc_statements(&context, false, &[]).is_err());
+    }
+}
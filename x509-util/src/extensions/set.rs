@@ -0,0 +1,124 @@
+use const_oid::ObjectIdentifier;
+use x509_cert::ext::Extension;
+
+use crate::error::{Error, Result};
+
+/// A set of extensions that rejects duplicate OIDs, as required by
+/// [RFC 5280 Section 4.2](https://www.rfc-editor.org/rfc/rfc5280#section-4.2).
+///
+/// Extensions are emitted ordered by the DER encoding of their OID, so the same set always
+/// encodes the same way regardless of insertion order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtensionSet<'a> {
+    extensions: Vec<Extension<'a>>,
+}
+
+impl<'a> ExtensionSet<'a> {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn push(&mut self, extension: Extension<'a>) -> Result<()> {
+        match self.position(&extension.extn_id) {
+            Ok(_) => Err(Error::DuplicateExtension(extension.extn_id)),
+            Err(index) => {
+                self.extensions.insert(index, extension);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn get(&self, oid: &ObjectIdentifier) -> Option<&Extension<'a>> {
+        self.position(oid).ok().map(|i| &self.extensions[i])
+    }
+
+    pub fn contains(&self, oid: &ObjectIdentifier) -> bool {
+        self.position(oid).is_ok()
+    }
+
+    pub fn remove(&mut self, oid: &ObjectIdentifier) -> Option<Extension<'a>> {
+        self.position(oid).ok().map(|i| self.extensions.remove(i))
+    }
+
+    pub fn len(&self) -> usize {
+        self.extensions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Extension<'a>> {
+        self.extensions.iter()
+    }
+
+    /// Returns `None` for an empty set, matching the optional `extensions` of a TBSCertificate
+    pub fn into_extensions(self) -> Option<x509_cert::ext::Extensions<'a>> {
+        if self.extensions.is_empty() {
+            None
+        } else {
+            Some(self.extensions)
+        }
+    }
+
+    fn position(&self, oid: &ObjectIdentifier) -> std::result::Result<usize, usize> {
+        self.extensions
+            .binary_search_by(|e| e.extn_id.as_bytes().cmp(oid.as_bytes()))
+    }
+}
+
+impl<'a> TryFrom<Vec<Extension<'a>>> for ExtensionSet<'a> {
+    type Error = Error;
+
+    fn try_from(extensions: Vec<Extension<'a>>) -> Result<Self> {
+        let mut set = ExtensionSet::new();
+
+        for extension in extensions {
+            set.push(extension)?;
+        }
+
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use x509_cert::ext::Extension;
+
+    use super::ExtensionSet;
+
+    fn extension(oid: const_oid::ObjectIdentifier) -> Extension<'static> {
+        Extension {
+            extn_id: oid,
+            critical: false,
+            extn_value: &[0x05, 0x00],
+        }
+    }
+
+    #[test]
+    fn test_extension_set() {
+        let mut set = ExtensionSet::new();
+
+        set.push(extension(const_oid::db::rfc5280::ID_CE_SUBJECT_ALT_NAME))
+            .unwrap();
+        set.push(extension(const_oid::db::rfc5280::ID_CE_BASIC_CONSTRAINTS))
+            .unwrap();
+        set.push(extension(
+            const_oid::db::rfc5280::ID_PE_AUTHORITY_INFO_ACCESS,
+        ))
+        .unwrap();
+
+        assert!(set
+            .push(extension(const_oid::db::rfc5280::ID_CE_SUBJECT_ALT_NAME))
+            .is_err());
+
+        let oids: Vec<_> = set.iter().map(|e| e.extn_id.to_string()).collect();
+        assert_eq!(oids, ["1.3.6.1.5.5.7.1.1", "2.5.29.17", "2.5.29.19"]);
+
+        assert!(set
+            .remove(&const_oid::db::rfc5280::ID_CE_SUBJECT_ALT_NAME)
+            .is_some());
+        assert_eq!(set.into_extensions().unwrap().len(), 2);
+        assert!(ExtensionSet::new().into_extensions().is_none());
+    }
+}
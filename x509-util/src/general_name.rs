@@ -0,0 +1,244 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use der::{
+    asn1::{Ia5StringRef, ObjectIdentifier, OctetStringRef},
+    AnyRef, Decode, Encode,
+};
+use x509_cert::ext::pkix::{
+    constraints::name::GeneralSubtree as X509GeneralSubtree,
+    name::{self, OtherName},
+};
+
+use crate::{
+    context::{Alloc, Context},
+    error::{Error, Result},
+};
+
+/// A single entry of a `GeneralNames` sequence, as used by SubjectAltName and friends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeneralName<'a> {
+    DnsName(&'a str),
+    IpAddress(IpAddr),
+    Rfc822Name(&'a str),
+    Uri(&'a str),
+    /// `value` must be the DER encoding of the otherName value, without the explicit `[0]` tag
+    OtherName {
+        type_id: ObjectIdentifier,
+        value: &'a [u8],
+    },
+    DirectoryName(x509_cert::name::Name<'a>),
+    RegisteredId(ObjectIdentifier),
+}
+
+impl<'a> GeneralName<'a> {
+    pub fn to_x509(&self, context: &'a Context) -> Result<name::GeneralName<'a>> {
+        Ok(match self {
+            GeneralName::DnsName(dns) => name::GeneralName::DnsName(Ia5StringRef::new(*dns)?),
+            GeneralName::IpAddress(ip) => {
+                let octets = match ip {
+                    IpAddr::V4(v4) => v4.octets().alloc_into(context),
+                    IpAddr::V6(v6) => v6.octets().alloc_into(context),
+                };
+
+                name::GeneralName::IpAddress(OctetStringRef::new(octets)?)
+            }
+            GeneralName::Rfc822Name(email) => {
+                name::GeneralName::Rfc822Name(Ia5StringRef::new(*email)?)
+            }
+            GeneralName::Uri(uri) => {
+                name::GeneralName::UniformResourceIdentifier(Ia5StringRef::new(*uri)?)
+            }
+            GeneralName::OtherName { type_id, value } => name::GeneralName::OtherName(OtherName {
+                type_id: *type_id,
+                value: AnyRef::from_der(value)?,
+            }),
+            GeneralName::DirectoryName(dn) => name::GeneralName::DirectoryName(dn.clone()),
+            GeneralName::RegisteredId(oid) => name::GeneralName::RegisteredId(*oid),
+        })
+    }
+
+    /// The inverse of [`GeneralName::to_x509`], `ediPartyName` is not supported
+    pub fn from_x509(context: &'a Context, general_name: &name::GeneralName<'a>) -> Result<Self> {
+        Ok(match general_name {
+            name::GeneralName::DnsName(dns) => GeneralName::DnsName(dns.as_str()),
+            name::GeneralName::IpAddress(ip) => {
+                let ip = match ip.as_bytes() {
+                    &[a, b, c, d] => IpAddr::V4(Ipv4Addr::new(a, b, c, d)),
+                    octets => match <[u8; 16]>::try_from(octets) {
+                        Ok(v6) => IpAddr::V6(Ipv6Addr::from(v6)),
+                        Err(_) => return Err(Error::InvalidIpAddressLength(octets.len())),
+                    },
+                };
+
+                GeneralName::IpAddress(ip)
+            }
+            name::GeneralName::Rfc822Name(email) => GeneralName::Rfc822Name(email.as_str()),
+            name::GeneralName::UniformResourceIdentifier(uri) => GeneralName::Uri(uri.as_str()),
+            name::GeneralName::OtherName(other) => GeneralName::OtherName {
+                type_id: other.type_id,
+                value: other.value.to_vec()?.alloc_into(context),
+            },
+            name::GeneralName::DirectoryName(dn) => GeneralName::DirectoryName(dn.clone()),
+            name::GeneralName::RegisteredId(oid) => GeneralName::RegisteredId(*oid),
+            name::GeneralName::EdiPartyName(_) => return Err(Error::UnsupportedGeneralName),
+        })
+    }
+
+    pub fn from_x509_names(
+        context: &'a Context,
+        names: &[name::GeneralName<'a>],
+    ) -> Result<Vec<Self>> {
+        names.iter().map(|n| Self::from_x509(context, n)).collect()
+    }
+
+    pub fn to_x509_names(
+        context: &'a Context,
+        names: &[GeneralName<'a>],
+    ) -> Result<name::GeneralNames<'a>> {
+        names.iter().map(|n| n.to_x509(context)).collect()
+    }
+}
+
+/// The base of a NameConstraints subtree, see
+/// [RFC 5280 Section 4.2.1.10](https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.10)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeneralSubtree<'a> {
+    /// Matches the host itself and any subdomain, e.g. `example.com`
+    DnsName(&'a str),
+    /// An address range in CIDR notation, host bits of the address are cleared
+    IpNetwork(IpAddr, u8),
+    /// A full mailbox, a host (`example.com`) or a domain (`.example.com`)
+    Rfc822Name(&'a str),
+    /// A host (`example.com`) or a domain (`.example.com`) of the URI authority
+    Uri(&'a str),
+    DirectoryName(x509_cert::name::Name<'a>),
+}
+
+impl<'a> GeneralSubtree<'a> {
+    pub fn to_x509(&self, context: &'a Context) -> Result<X509GeneralSubtree<'a>> {
+        let base = match self {
+            GeneralSubtree::DnsName(dns) => name::GeneralName::DnsName(Ia5StringRef::new(*dns)?),
+            GeneralSubtree::IpNetwork(ip, prefix) => {
+                let octets = match ip {
+                    IpAddr::V4(v4) => network_octets(&v4.octets(), *prefix)?,
+                    IpAddr::V6(v6) => network_octets(&v6.octets(), *prefix)?,
+                };
+
+                name::GeneralName::IpAddress(OctetStringRef::new(octets.alloc_into(context))?)
+            }
+            GeneralSubtree::Rfc822Name(email) => {
+                name::GeneralName::Rfc822Name(Ia5StringRef::new(*email)?)
+            }
+            GeneralSubtree::Uri(uri) => {
+                name::GeneralName::UniformResourceIdentifier(Ia5StringRef::new(*uri)?)
+            }
+            GeneralSubtree::DirectoryName(dn) => name::GeneralName::DirectoryName(dn.clone()),
+        };
+
+        Ok(X509GeneralSubtree {
+            base,
+            minimum: 0,
+            maximum: None,
+        })
+    }
+
+    /// The inverse of [`GeneralSubtree::to_x509`], `minimum` and `maximum` must have their
+    /// default values as required by RFC 5280
+    pub fn from_x509(subtree: &X509GeneralSubtree<'a>) -> Result<Self> {
+        if subtree.minimum != 0 || subtree.maximum.is_some() {
+            return Err(Error::UnsupportedSubtreeBounds);
+        }
+
+        Ok(match &subtree.base {
+            name::GeneralName::DnsName(dns) => GeneralSubtree::DnsName(dns.as_str()),
+            name::GeneralName::IpAddress(ip) => {
+                // The address followed by a netmask of the same length
+                let (address, mask) = ip.as_bytes().split_at(ip.as_bytes().len() / 2);
+                let address = match (address, <[u8; 16]>::try_from(address)) {
+                    (&[a, b, c, d], _) if mask.len() == 4 => IpAddr::V4(Ipv4Addr::new(a, b, c, d)),
+                    (_, Ok(v6)) if mask.len() == 16 => IpAddr::V6(Ipv6Addr::from(v6)),
+                    _ => return Err(Error::InvalidIpAddressLength(ip.as_bytes().len())),
+                };
+
+                GeneralSubtree::IpNetwork(address, netmask_prefix(mask)?)
+            }
+            name::GeneralName::Rfc822Name(email) => GeneralSubtree::Rfc822Name(email.as_str()),
+            name::GeneralName::UniformResourceIdentifier(uri) => GeneralSubtree::Uri(uri.as_str()),
+            name::GeneralName::DirectoryName(dn) => GeneralSubtree::DirectoryName(dn.clone()),
+            _ => return Err(Error::UnsupportedGeneralName),
+        })
+    }
+}
+
+/// Encodes an address and prefix length as the address followed by its netmask
+fn network_octets(address: &[u8], prefix: u8) -> Result<Vec<u8>> {
+    if usize::from(prefix) > address.len() * 8 {
+        return Err(Error::InvalidIpPrefix(prefix));
+    }
+
+    let mask: Vec<u8> = (0..address.len())
+        .map(|i| {
+            let bits = usize::from(prefix).saturating_sub(i * 8).min(8);
+            (0xffu16 << (8 - bits)) as u8
+        })
+        .collect();
+
+    Ok(address
+        .iter()
+        .zip(mask.iter())
+        .map(|(a, m)| a & m)
+        .chain(mask.iter().copied())
+        .collect())
+}
+
+/// The prefix length of a netmask as encoded by [`network_octets`]
+fn netmask_prefix(mask: &[u8]) -> Result<u8> {
+    let ones: u32 = mask.iter().map(|b| b.count_ones()).sum();
+    let leading: u32 = mask
+        .iter()
+        .scan(true, |contiguous, b| {
+            let bits = if *contiguous { b.leading_ones() } else { 0 };
+            *contiguous = *b == 0xff;
+            Some(bits)
+        })
+        .sum();
+
+    if ones != leading {
+        return Err(Error::InvalidNetmask);
+    }
+
+    Ok(ones as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{netmask_prefix, network_octets};
+
+    #[test]
+    fn test_network_octets() {
+        assert_eq!(
+            network_octets(&[10, 1, 2, 3], 8).unwrap(),
+            [10, 0, 0, 0, 255, 0, 0, 0]
+        );
+        assert_eq!(
+            network_octets(&[192, 168, 1, 1], 23).unwrap(),
+            [192, 168, 0, 0, 255, 255, 254, 0]
+        );
+        assert_eq!(
+            network_octets(&[1, 2, 3, 4], 32).unwrap(),
+            [1, 2, 3, 4, 255, 255, 255, 255]
+        );
+        assert_eq!(network_octets(&[1, 2, 3, 4], 0).unwrap(), [0; 8]);
+        assert!(network_octets(&[1, 2, 3, 4], 33).is_err());
+    }
+
+    #[test]
+    fn test_netmask_prefix() {
+        assert_eq!(netmask_prefix(&[255, 0, 0, 0]).unwrap(), 8);
+        assert_eq!(netmask_prefix(&[255, 255, 254, 0]).unwrap(), 23);
+        assert_eq!(netmask_prefix(&[0xff; 16]).unwrap(), 128);
+        assert_eq!(netmask_prefix(&[0; 4]).unwrap(), 0);
+        assert!(netmask_prefix(&[255, 0, 255, 0]).is_err());
+        assert!(netmask_prefix(&[0xf0, 0x0f, 0, 0]).is_err());
+    }
+}
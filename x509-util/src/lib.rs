@@ -1,18 +1,24 @@
 use context::Alloc;
 use der::asn1::UIntRef;
 
+pub use const_oid;
 pub use x509_cert;
 
 pub mod algorithm_identifier;
+pub mod consistency;
 pub mod context;
+pub mod ct;
 pub mod error;
 pub mod extensions;
+pub mod general_name;
 pub mod name;
 pub mod pem;
 pub mod prelude;
 mod rsa_pss_params;
 pub mod signer;
 pub mod subject_public_key_info;
+#[cfg(test)]
+mod testing;
 pub mod validity;
 
 pub fn random_serial(context: &context::Context) -> error::Result<UIntRef> {
@@ -1,4 +1,5 @@
-use der::asn1::Utf8StringRef;
+use const_oid::ObjectIdentifier;
+use der::{asn1::AnyRef, Decode, Encode, Tag, Tagged};
 use x509_cert::{
     attr::{AttributeType, AttributeTypeAndValue},
     name::{RdnSequence, RelativeDistinguishedName},
@@ -6,44 +7,788 @@ use x509_cert::{
 
 use crate::{
     context::{Alloc, Context},
-    error::Result,
+    error::{Error, Result},
 };
 
+mod compare;
+
+/// X.520 `organizationIdentifier`
+const ORGANIZATION_IDENTIFIER: AttributeType = ObjectIdentifier::new_unwrap("2.5.4.97");
+/// PKCS #9 `unstructuredName`
+const UNSTRUCTURED_NAME: AttributeType = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.2");
+/// EV guidelines `jurisdictionLocalityName`
+const JURISDICTION_L: AttributeType = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.60.2.1.1");
+/// EV guidelines `jurisdictionStateOrProvinceName`
+const JURISDICTION_ST: AttributeType = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.60.2.1.2");
+/// EV guidelines `jurisdictionCountryName`
+const JURISDICTION_C: AttributeType = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.60.2.1.3");
+
+/// String types an attribute value can be encoded as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringEncoding {
+    Printable,
+    Utf8,
+    Ia5,
+    /// UCS-2, limited to the basic multilingual plane
+    Bmp,
+}
+
+impl StringEncoding {
+    /// The encoding mandated by RFC 5280 Appendix A for `attribute`, UTF8String for any
+    /// DirectoryString, as required by
+    /// [RFC 5280 Section 4.1.2.6](https://www.rfc-editor.org/rfc/rfc5280#section-4.1.2.6)
+    pub fn default_for(attribute: AttributeType) -> Self {
+        match attribute {
+            const_oid::db::rfc4519::C
+            | const_oid::db::rfc4519::SERIAL_NUMBER
+            | const_oid::db::rfc4519::DN_QUALIFIER
+            | JURISDICTION_C => StringEncoding::Printable,
+            const_oid::db::rfc4519::DC | const_oid::db::rfc3280::EMAIL_ADDRESS => {
+                StringEncoding::Ia5
+            }
+            _ => StringEncoding::Utf8,
+        }
+    }
+
+    /// Encodes `value`, failing if it contains characters outside of the character set
+    pub fn encode<'a>(&self, context: &'a Context, value: &str) -> Result<AnyRef<'a>> {
+        let fits = match self {
+            StringEncoding::Printable => value.bytes().all(is_printable),
+            StringEncoding::Ia5 => value.is_ascii(),
+            StringEncoding::Utf8 => true,
+            StringEncoding::Bmp => value.chars().all(|c| (c as u32) <= 0xffff),
+        };
+
+        if !fits {
+            return Err(Error::InvalidStringEncoding(*self, value.to_string()));
+        }
+
+        let (tag, bytes) = match self {
+            StringEncoding::Printable => (Tag::PrintableString, value.as_bytes().to_vec()),
+            StringEncoding::Utf8 => (Tag::Utf8String, value.as_bytes().to_vec()),
+            StringEncoding::Ia5 => (Tag::Ia5String, value.as_bytes().to_vec()),
+            StringEncoding::Bmp => (
+                Tag::BmpString,
+                value.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+            ),
+        };
+
+        Ok(AnyRef::new(tag, bytes.alloc_into(context))?)
+    }
+}
+
+/// The PrintableString character set of X.680
+fn is_printable(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b" '()+,-./:=?".contains(&b)
+}
+
 pub struct Name;
 
 impl Name {
+    /// Builds a name with one attribute per RDN. Values are encoded with
+    /// [`StringEncoding::default_for`] their attribute type.
     pub fn from_pairs<'a, T: AsRef<str>, U: AsRef<str>>(
         context: &'a Context,
         pairs: &[(T, U)],
     ) -> Result<x509_cert::name::Name<'a>> {
-        let mut rdns = Vec::with_capacity(pairs.len());
+        let rdns: Vec<_> = pairs.iter().map(std::slice::from_ref).collect();
 
-        for (key, val) in pairs.iter() {
-            let value: Utf8StringRef =
-                Utf8StringRef::new(val.as_ref().as_bytes().alloc_into(context))?;
+        Self::from_rdns(context, &rdns)
+    }
 
-            if let Some(key) = get_attribute_type(key.as_ref()) {
-                let atv = AttributeTypeAndValue {
-                    oid: key,
-                    value: value.into(),
-                };
+    /// Like [`Name::from_pairs`], with an explicit string encoding per value
+    pub fn from_pairs_with_encodings<'a, U: AsRef<str>>(
+        context: &'a Context,
+        pairs: &[(AttributeType, U, StringEncoding)],
+    ) -> Result<x509_cert::name::Name<'a>> {
+        let rdns: Vec<_> = pairs.iter().map(std::slice::from_ref).collect();
 
-                rdns.push(RelativeDistinguishedName([atv].try_into()?));
-            }
+        Self::from_rdns_with_encodings(context, &rdns)
+    }
+
+    /// Builds a name from RDNs that may hold several attributes each, e.g. `CN=foo+UID=123`.
+    /// Attributes within an RDN are sorted into DER `SET OF` order.
+    pub fn from_rdns<'a, R: AsRef<[(T, U)]>, T: AsRef<str>, U: AsRef<str>>(
+        context: &'a Context,
+        rdns: &[R],
+    ) -> Result<x509_cert::name::Name<'a>> {
+        let rdns = rdns
+            .iter()
+            .map(|rdn| {
+                rdn.as_ref()
+                    .iter()
+                    .map(|(key, val)| {
+                        let oid = attribute_type(key.as_ref())?;
+
+                        Ok((oid, val.as_ref(), StringEncoding::default_for(oid)))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::from_rdns_with_encodings(context, &rdns)
+    }
+
+    pub fn from_rdns_with_encodings<
+        'a,
+        R: AsRef<[(AttributeType, U, StringEncoding)]>,
+        U: AsRef<str>,
+    >(
+        context: &'a Context,
+        rdns: &[R],
+    ) -> Result<x509_cert::name::Name<'a>> {
+        Self::build(context, rdns, true)
+    }
+
+    /// Like [`Name::from_rdns_with_encodings`], without [`validate_attribute`]. Reproduces the
+    /// name of an existing certificate byte for byte, even if it breaks the bounds or formats,
+    /// e.g. `C=us` or a CN over 64 characters.
+    pub fn from_rdns_with_encodings_unchecked<
+        'a,
+        R: AsRef<[(AttributeType, U, StringEncoding)]>,
+        U: AsRef<str>,
+    >(
+        context: &'a Context,
+        rdns: &[R],
+    ) -> Result<x509_cert::name::Name<'a>> {
+        Self::build(context, rdns, false)
+    }
+
+    fn build<'a, R: AsRef<[(AttributeType, U, StringEncoding)]>, U: AsRef<str>>(
+        context: &'a Context,
+        rdns: &[R],
+        validate: bool,
+    ) -> Result<x509_cert::name::Name<'a>> {
+        let rdns = rdns
+            .iter()
+            .map(|rdn| {
+                let atvs = rdn
+                    .as_ref()
+                    .iter()
+                    .map(|(oid, val, encoding)| {
+                        if validate {
+                            validate_attribute(*oid, val.as_ref())?;
+                        }
+
+                        Ok(AttributeTypeAndValue {
+                            oid: *oid,
+                            value: encoding.encode(context, val.as_ref())?,
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                if atvs.is_empty() {
+                    return Err(Error::EmptyRelativeDistinguishedName);
+                }
+
+                Ok(RelativeDistinguishedName(atvs.try_into()?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(RdnSequence(rdns))
+    }
+
+    /// Parses the string representation of a distinguished name, see
+    /// [RFC 4514 Section 3](https://www.rfc-editor.org/rfc/rfc4514#section-3).
+    ///
+    /// The string lists the RDNs starting with the last one of the `RDNSequence`, so
+    /// `CN=example.com,O=Example,C=US` encodes `C` first. String values are encoded with
+    /// [`StringEncoding::default_for`] their attribute type, `#` prefixed values are taken as
+    /// the DER encoding of the value.
+    pub fn parse_rfc4514<'a>(context: &'a Context, s: &str) -> Result<x509_cert::name::Name<'a>> {
+        Self::parse(context, s, true)
+    }
+
+    /// Like [`Name::parse_rfc4514`], without [`validate_attribute`], for the string form of
+    /// existing names
+    pub fn parse_rfc4514_unchecked<'a>(
+        context: &'a Context,
+        s: &str,
+    ) -> Result<x509_cert::name::Name<'a>> {
+        Self::parse(context, s, false)
+    }
+
+    fn parse<'a>(
+        context: &'a Context,
+        s: &str,
+        validate: bool,
+    ) -> Result<x509_cert::name::Name<'a>> {
+        if s.trim().is_empty() {
+            return Ok(RdnSequence(Vec::new()));
         }
 
+        let mut rdns = split_unescaped(s, b',')?
+            .into_iter()
+            .map(|rdn| {
+                let atvs = split_unescaped(rdn, b'+')?
+                    .into_iter()
+                    .map(|atv| parse_attribute(context, atv, validate))
+                    .collect::<Result<Vec<_>>>()?;
+
+                Ok(RelativeDistinguishedName(atvs.try_into()?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        rdns.reverse();
+
         Ok(RdnSequence(rdns))
     }
+
+    /// Formats a distinguished name as described in
+    /// [RFC 4514 Section 2](https://www.rfc-editor.org/rfc/rfc4514#section-2)
+    pub fn to_rfc4514(name: &x509_cert::name::Name<'_>) -> Result<String> {
+        let rdns = name
+            .0
+            .iter()
+            .rev()
+            .map(|rdn| {
+                let atvs = rdn
+                    .0
+                    .iter()
+                    .map(format_attribute)
+                    .collect::<Result<Vec<_>>>()?;
+
+                Ok(atvs.join("+"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(rdns.join(","))
+    }
+}
+
+/// Known attribute types from RFC 4519, X.520, PKCS #9 and the CA/Browser Forum EV
+/// guidelines. The first name listed for an OID is the one used when formatting.
+const ATTRIBUTE_TYPES: &[(&str, AttributeType)] = &[
+    ("C", const_oid::db::rfc4519::C),
+    ("countryName", const_oid::db::rfc4519::C),
+    ("ST", const_oid::db::rfc4519::ST),
+    ("stateOrProvinceName", const_oid::db::rfc4519::ST),
+    ("L", const_oid::db::rfc4519::L),
+    ("localityName", const_oid::db::rfc4519::L),
+    ("O", const_oid::db::rfc4519::O),
+    ("organizationName", const_oid::db::rfc4519::O),
+    ("OU", const_oid::db::rfc4519::OU),
+    ("organizationalUnitName", const_oid::db::rfc4519::OU),
+    ("CN", const_oid::db::rfc4519::CN),
+    ("commonName", const_oid::db::rfc4519::CN),
+    ("STREET", const_oid::db::rfc4519::STREET),
+    ("streetAddress", const_oid::db::rfc4519::STREET),
+    ("DC", const_oid::db::rfc4519::DC),
+    ("domainComponent", const_oid::db::rfc4519::DC),
+    ("UID", const_oid::db::rfc4519::UID),
+    ("userId", const_oid::db::rfc4519::UID),
+    ("serialNumber", const_oid::db::rfc4519::SERIAL_NUMBER),
+    ("title", const_oid::db::rfc4519::TITLE),
+    ("description", const_oid::db::rfc4519::DESCRIPTION),
+    (
+        "businessCategory",
+        const_oid::db::rfc4519::BUSINESS_CATEGORY,
+    ),
+    ("postalCode", const_oid::db::rfc4519::POSTAL_CODE),
+    ("postOfficeBox", const_oid::db::rfc4519::POST_OFFICE_BOX),
+    ("SN", const_oid::db::rfc4519::SN),
+    ("surname", const_oid::db::rfc4519::SN),
+    ("name", const_oid::db::rfc4519::NAME),
+    ("givenName", const_oid::db::rfc4519::GIVEN_NAME),
+    ("GN", const_oid::db::rfc4519::GIVEN_NAME),
+    ("initials", const_oid::db::rfc4519::INITIALS),
+    (
+        "generationQualifier",
+        const_oid::db::rfc4519::GENERATION_QUALIFIER,
+    ),
+    ("dnQualifier", const_oid::db::rfc4519::DN_QUALIFIER),
+    ("pseudonym", const_oid::db::rfc3280::PSEUDONYM),
+    ("organizationIdentifier", ORGANIZATION_IDENTIFIER),
+    ("emailAddress", const_oid::db::rfc3280::EMAIL_ADDRESS),
+    ("E", const_oid::db::rfc3280::EMAIL_ADDRESS),
+    ("unstructuredName", UNSTRUCTURED_NAME),
+    ("jurisdictionL", JURISDICTION_L),
+    ("jurisdictionLocalityName", JURISDICTION_L),
+    ("jurisdictionST", JURISDICTION_ST),
+    ("jurisdictionStateOrProvinceName", JURISDICTION_ST),
+    ("jurisdictionC", JURISDICTION_C),
+    ("jurisdictionCountryName", JURISDICTION_C),
+];
+
+/// Resolves an attribute name, case insensitively, or a dotted OID
+pub fn attribute_type(key: &str) -> Result<AttributeType> {
+    let key = key.trim();
+
+    if key.starts_with(|c: char| c.is_ascii_digit()) {
+        return ObjectIdentifier::new(key)
+            .map_err(|_| Error::UnknownAttributeType(key.to_string()));
+    }
+
+    ATTRIBUTE_TYPES
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, oid)| *oid)
+        .ok_or_else(|| Error::UnknownAttributeType(key.to_string()))
+}
+
+/// The short name of a known attribute type
+pub fn attribute_name(oid: AttributeType) -> Option<&'static str> {
+    ATTRIBUTE_TYPES
+        .iter()
+        .find(|(_, o)| *o == oid)
+        .map(|(name, _)| *name)
+}
+
+/// Upper bounds, in characters, from RFC 5280 Appendix A, X.520 and RFC 4519
+const UPPER_BOUNDS: &[(AttributeType, usize)] = &[
+    (const_oid::db::rfc4519::CN, 64),
+    (const_oid::db::rfc4519::L, 128),
+    (const_oid::db::rfc4519::ST, 128),
+    (const_oid::db::rfc4519::STREET, 128),
+    (const_oid::db::rfc4519::O, 64),
+    (const_oid::db::rfc4519::OU, 64),
+    (const_oid::db::rfc4519::TITLE, 64),
+    (const_oid::db::rfc4519::SERIAL_NUMBER, 64),
+    (const_oid::db::rfc4519::SN, 32768),
+    (const_oid::db::rfc4519::NAME, 32768),
+    (const_oid::db::rfc4519::GIVEN_NAME, 32768),
+    (const_oid::db::rfc4519::INITIALS, 32768),
+    (const_oid::db::rfc4519::GENERATION_QUALIFIER, 32768),
+    (const_oid::db::rfc4519::DESCRIPTION, 1024),
+    (const_oid::db::rfc4519::BUSINESS_CATEGORY, 128),
+    (const_oid::db::rfc4519::POSTAL_CODE, 40),
+    (const_oid::db::rfc4519::POST_OFFICE_BOX, 40),
+    (const_oid::db::rfc3280::PSEUDONYM, 128),
+    (const_oid::db::rfc4519::UID, 256),
+    (const_oid::db::rfc4519::DC, 63),
+    (const_oid::db::rfc3280::EMAIL_ADDRESS, 255),
+    (JURISDICTION_L, 128),
+    (JURISDICTION_ST, 128),
+];
+
+/// Checks `value` against the size bounds and format of a known attribute type. Values of
+/// unknown attribute types are accepted as is.
+pub fn validate_attribute(oid: AttributeType, value: &str) -> Result<()> {
+    let Some(name) = attribute_name(oid) else {
+        return Ok(());
+    };
+
+    let invalid = |reason| {
+        Err(Error::InvalidAttributeValue(
+            name,
+            value.to_string(),
+            reason,
+        ))
+    };
+
+    if value.is_empty() {
+        return invalid("must not be empty");
+    }
+
+    let length = value.chars().count();
+    let upper_bound = UPPER_BOUNDS
+        .iter()
+        .find(|(o, _)| *o == oid)
+        .map(|(_, ub)| *ub);
+
+    if let Some(upper_bound) = upper_bound.filter(|ub| length > *ub) {
+        return Err(Error::AttributeTooLong(name, upper_bound, length));
+    }
+
+    match oid {
+        const_oid::db::rfc4519::C | JURISDICTION_C
+            if length != 2 || !value.bytes().all(|b| b.is_ascii_uppercase()) =>
+        {
+            invalid("expected a two letter ISO 3166 country code")
+        }
+        const_oid::db::rfc4519::DC if value.contains('.') => {
+            invalid("expected a single domain label")
+        }
+        const_oid::db::rfc3280::EMAIL_ADDRESS if !matches!(value.split_once('@'), Some((local, domain)) if !local.is_empty() && !domain.is_empty()) => {
+            invalid("expected an email address")
+        }
+        _ => Ok(()),
+    }
 }
 
-fn get_attribute_type(key: &str) -> Option<AttributeType> {
-    match key {
-        "C" => Some(const_oid::db::rfc4519::C),
-        "ST" => Some(const_oid::db::rfc4519::ST),
-        "L" => Some(const_oid::db::rfc4519::L),
-        "O" => Some(const_oid::db::rfc4519::O),
-        "OU" => Some(const_oid::db::rfc4519::OU),
-        "CN" => Some(const_oid::db::rfc4519::CN),
+/// Splits on `separator`, skipping separators escaped with a backslash
+fn split_unescaped(s: &str, separator: u8) -> Result<Vec<&str>> {
+    let bytes = s.as_bytes();
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b if b == separator => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+
+    if i > bytes.len() {
+        return Err(Error::InvalidRfc4514(s.to_string()));
+    }
+
+    parts.push(&s[start..]);
+
+    Ok(parts)
+}
+
+fn parse_attribute<'a>(
+    context: &'a Context,
+    atv: &str,
+    validate: bool,
+) -> Result<AttributeTypeAndValue<'a>> {
+    let (key, value) = atv
+        .split_once('=')
+        .ok_or_else(|| Error::InvalidRfc4514(atv.to_string()))?;
+
+    let oid = attribute_type(key)?;
+
+    let value = value.trim_start_matches(' ');
+
+    let value = match value.strip_prefix('#') {
+        Some(hex) => {
+            let der = decode_hex(hex.trim_end_matches(' '))
+                .ok_or_else(|| Error::InvalidRfc4514(atv.to_string()))?;
+
+            AnyRef::from_der(der.alloc_into(context))?
+        }
+        None => {
+            let value = unescape(value).ok_or_else(|| Error::InvalidRfc4514(atv.to_string()))?;
+            if validate {
+                validate_attribute(oid, &value)?;
+            }
+
+            StringEncoding::default_for(oid).encode(context, &value)?
+        }
+    };
+
+    Ok(AttributeTypeAndValue { oid, value })
+}
+
+/// Resolves `\<special>` and `\<hex><hex>` escapes and drops unescaped trailing spaces
+fn unescape(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut significant = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            let escaped = *bytes.get(i + 1)?;
+
+            if escaped.is_ascii_hexdigit() {
+                out.push(decode_hex(value.get(i + 1..i + 3)?)?[0]);
+                i += 3;
+            } else if b" \"#+,;<=>\\".contains(&escaped) {
+                out.push(escaped);
+                i += 2;
+            } else {
+                return None;
+            }
+
+            significant = out.len();
+        } else {
+            out.push(bytes[i]);
+            if bytes[i] != b' ' {
+                significant = out.len();
+            }
+            i += 1;
+        }
+    }
+
+    out.truncate(significant);
+
+    String::from_utf8(out).ok()
+}
+
+fn format_attribute(atv: &AttributeTypeAndValue<'_>) -> Result<String> {
+    let key = match attribute_name(atv.oid) {
+        Some(name) => name.to_string(),
+        None => atv.oid.to_string(),
+    };
+
+    let value = match value_as_string(&atv.value) {
+        Some(value) => escape(&value),
+        None => format!("#{}", encode_hex(&atv.value.to_vec()?)),
+    };
+
+    Ok(format!("{}={}", key, value))
+}
+
+/// Decodes the directory string types, other values are formatted as `#` DER
+fn value_as_string(value: &AnyRef<'_>) -> Option<String> {
+    match value.tag() {
+        Tag::Utf8String | Tag::PrintableString | Tag::Ia5String | Tag::VisibleString => {
+            std::str::from_utf8(value.value()).ok().map(str::to_string)
+        }
+        // Treated as Latin-1, like OpenSSL does
+        Tag::TeletexString => Some(value.value().iter().map(|b| char::from(*b)).collect()),
+        Tag::BmpString => {
+            let units: Vec<u16> = value
+                .value()
+                .chunks(2)
+                .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]))
+                .collect();
+
+            String::from_utf16(&units).ok()
+        }
         _ => None,
     }
 }
+
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let last = value.chars().count().saturating_sub(1);
+
+    for (i, c) in value.chars().enumerate() {
+        match c {
+            '"' | '+' | ',' | ';' | '<' | '>' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '#' | ' ' if i == 0 => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' ' if i == last => out.push_str("\\ "),
+            '\0' => out.push_str("\\00"),
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [hi, lo] => {
+                Some(((*hi as char).to_digit(16)? << 4 | (*lo as char).to_digit(16)?) as u8)
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use der::{Tag, Tagged};
+
+    use super::{Name, StringEncoding};
+    use crate::{context::Context, error::Error};
+
+    fn roundtrip(input: &str, expected: &str) {
+        let context = Context::new();
+
+        let name = Name::parse_rfc4514(&context, input).unwrap();
+
+        assert_eq!(Name::to_rfc4514(&name).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_rfc4514_roundtrip() {
+        // Examples from RFC 4514 Section 4
+        roundtrip(
+            "UID=jsmith,DC=example,DC=net",
+            "UID=jsmith,DC=example,DC=net",
+        );
+        roundtrip(
+            "OU=Sales+CN=J.  Smith,DC=example,DC=net",
+            "OU=Sales+CN=J.  Smith,DC=example,DC=net",
+        );
+        roundtrip(
+            "CN=James \\\"Jim\\\" Smith\\, III,DC=example,DC=net",
+            "CN=James \\\"Jim\\\" Smith\\, III,DC=example,DC=net",
+        );
+        roundtrip(
+            "CN=Before\\0dAfter,DC=example,DC=net",
+            "CN=Before\rAfter,DC=example,DC=net",
+        );
+        roundtrip(
+            "1.3.6.1.4.1.1466.0=#04024869",
+            "1.3.6.1.4.1.1466.0=#04024869",
+        );
+        roundtrip("CN=Lu\\C4\\8Di\\C4\\87", "CN=Lučić");
+
+        // `openssl x509 -subject -nameopt RFC2253` and leading/trailing spaces
+        roundtrip(
+            "CN=\\ leading and trailing\\ ,O=Example\\+Co, C = US",
+            "CN=\\ leading and trailing\\ ,O=Example\\+Co,C=US",
+        );
+        roundtrip("CN=\\#1", "CN=\\#1");
+        roundtrip(
+            "CN=a\\+b,O=Example\\, Inc.,C=US",
+            "CN=a\\+b,O=Example\\, Inc.,C=US",
+        );
+        roundtrip("", "");
+
+        // Attributes of a multi-valued RDN are ordered by their DER encoding
+        roundtrip("CN=J.  Smith+OU=Sales", "OU=Sales+CN=J.  Smith");
+    }
+
+    #[test]
+    fn test_rfc4514_order() {
+        let context = Context::new();
+
+        let name = Name::parse_rfc4514(&context, "CN=example.com,O=Example,C=US").unwrap();
+        let pairs = Name::from_pairs(
+            &context,
+            &[("C", "US"), ("O", "Example"), ("CN", "example.com")],
+        )
+        .unwrap();
+
+        assert_eq!(name, pairs);
+    }
+
+    #[test]
+    fn test_attribute_types() {
+        let context = Context::new();
+
+        let name = Name::from_pairs(
+            &context,
+            &[
+                ("DC", "com"),
+                ("dc", "example"),
+                ("emailAddress", "admin@example.com"),
+                ("jurisdictionC", "US"),
+                ("2.5.4.5", "1234"),
+                ("1.2.3.4", "private"),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            Name::to_rfc4514(&name).unwrap(),
+            "1.2.3.4=private,serialNumber=1234,jurisdictionC=US,\
+             emailAddress=admin@example.com,DC=example,DC=com"
+        );
+
+        assert!(Name::from_pairs(&context, &[("XYZ", "foo")]).is_err());
+        assert!(Name::from_pairs(&context, &[("1.2.", "foo")]).is_err());
+    }
+
+    #[test]
+    fn test_string_encodings() {
+        let context = Context::new();
+
+        let name = Name::from_pairs(
+            &context,
+            &[("C", "US"), ("DC", "example"), ("CN", "Łukasz")],
+        )
+        .unwrap();
+        let tags: Vec<_> = name
+            .0
+            .iter()
+            .map(|rdn| rdn.0.get(0).unwrap().value.tag())
+            .collect();
+        assert_eq!(
+            tags,
+            [Tag::PrintableString, Tag::Ia5String, Tag::Utf8String]
+        );
+
+        let name = Name::from_pairs_with_encodings(
+            &context,
+            &[(const_oid::db::rfc4519::CN, "Łukasz", StringEncoding::Bmp)],
+        )
+        .unwrap();
+        assert_eq!(
+            name.0[0].0.get(0).unwrap().value.value(),
+            [0x01, 0x41, 0x00, 0x75, 0x00, 0x6b, 0x00, 0x61, 0x00, 0x73, 0x00, 0x7a]
+        );
+        assert_eq!(Name::to_rfc4514(&name).unwrap(), "CN=Łukasz");
+
+        assert!(Name::from_pairs(&context, &[("C", "U&")]).is_err());
+        assert!(Name::from_pairs(&context, &[("emailAddress", "ł@example.com")]).is_err());
+        assert!(StringEncoding::Bmp.encode(&context, "🦀").is_err());
+    }
+
+    #[test]
+    fn test_multi_valued_rdn() {
+        let context = Context::new();
+
+        let name = Name::from_rdns(
+            &context,
+            &[vec![("DC", "example")], vec![("UID", "123"), ("CN", "foo")]],
+        )
+        .unwrap();
+
+        assert_eq!(name.0.len(), 2);
+        assert_eq!(name.0[1].0.len(), 2);
+        assert_eq!(
+            name,
+            Name::parse_rfc4514(&context, "CN=foo+UID=123,DC=example").unwrap()
+        );
+        // CN sorts before UID, its DER encoding is shorter
+        assert_eq!(
+            Name::to_rfc4514(&name).unwrap(),
+            "CN=foo+UID=123,DC=example"
+        );
+
+        let empty: [Vec<(&str, &str)>; 1] = [vec![]];
+        assert!(Name::from_rdns(&context, &empty).is_err());
+    }
+
+    #[test]
+    fn test_attribute_validation() {
+        let context = Context::new();
+
+        let cn = "a".repeat(64);
+        assert!(Name::from_pairs(&context, &[("CN", cn.as_str())]).is_ok());
+
+        let cn = "ł".repeat(65);
+        assert!(matches!(
+            Name::from_pairs(&context, &[("CN", cn.as_str())]),
+            Err(Error::AttributeTooLong("CN", 64, 65))
+        ));
+        assert!(matches!(
+            Name::parse_rfc4514(&context, "C=USA"),
+            Err(Error::InvalidAttributeValue("C", _, _))
+        ));
+        assert!(Name::from_pairs(&context, &[("C", "us")]).is_err());
+        assert!(Name::from_pairs(&context, &[("O", "")]).is_err());
+        assert!(Name::from_pairs(&context, &[("DC", "example.com")]).is_err());
+        assert!(Name::from_pairs(&context, &[("emailAddress", "admin")]).is_err());
+
+        // Unknown attribute types and DER values are not checked
+        assert!(Name::from_pairs(&context, &[("1.2.3.4", "")]).is_ok());
+        assert!(Name::parse_rfc4514(&context, "C=#1303555341").is_ok());
+
+        // Existing names are rebuilt as they are
+        let name = Name::from_rdns_with_encodings_unchecked(
+            &context,
+            &[
+                [(const_oid::db::rfc4519::C, "us", StringEncoding::Printable)],
+                [(
+                    const_oid::db::rfc4519::CN,
+                    cn.as_str(),
+                    StringEncoding::Utf8,
+                )],
+            ],
+        )
+        .unwrap();
+        assert_eq!(Name::to_rfc4514(&name).unwrap(), format!("CN={},C=us", cn));
+        assert_eq!(
+            Name::parse_rfc4514_unchecked(&context, &format!("CN={},C=us", cn)).unwrap(),
+            name
+        );
+    }
+
+    #[test]
+    fn test_rfc4514_invalid() {
+        let context = Context::new();
+
+        assert!(Name::parse_rfc4514(&context, "CN=trailing\\").is_err());
+        assert!(Name::parse_rfc4514(&context, "CN=bad\\escape").is_err());
+        assert!(Name::parse_rfc4514(&context, "FOO=bar").is_err());
+        assert!(Name::parse_rfc4514(&context, "CN").is_err());
+        assert!(Name::parse_rfc4514(&context, "CN=#zz").is_err());
+    }
+}
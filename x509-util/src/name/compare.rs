@@ -0,0 +1,216 @@
+use der::{asn1::AnyRef, Encode, Tag};
+use sha1::{Digest, Sha1};
+use x509_cert::{
+    attr::{AttributeType, AttributeTypeAndValue},
+    name::RelativeDistinguishedName,
+};
+
+use super::{value_as_string, Name};
+use crate::error::Result;
+
+/// An attribute value prepared for comparison
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Prepared {
+    String(String),
+    Der(Vec<u8>),
+}
+
+impl Name {
+    /// Compares two names as described in
+    /// [RFC 5280 Section 7.1](https://www.rfc-editor.org/rfc/rfc5280#section-7.1)
+    ///
+    /// String values are prepared following the caseIgnoreMatch rules of
+    /// [RFC 4518](https://www.rfc-editor.org/rfc/rfc4518), so the string type, case and
+    /// insignificant spaces don't matter. Unicode normalization (NFKC) is not applied. The
+    /// attributes of a multi-valued RDN are compared as a set, other values must have the same
+    /// DER encoding. Names with values that can't be encoded are never equal.
+    pub fn eq_normalized(a: &x509_cert::name::Name<'_>, b: &x509_cert::name::Name<'_>) -> bool {
+        a.0.len() == b.0.len()
+            && a.0
+                .iter()
+                .zip(&b.0)
+                .all(|(a, b)| match (prepare_rdn(a), prepare_rdn(b)) {
+                    (Ok(a), Ok(b)) => a == b,
+                    _ => false,
+                })
+    }
+
+    /// Hashes a name like `X509_NAME_hash` in OpenSSL, as used by `openssl x509 -subject_hash`
+    ///
+    /// `c_rehash` style directories name certificates `{:08x}.0` after the hash of their subject.
+    pub fn openssl_hash(name: &x509_cert::name::Name<'_>) -> Result<u32> {
+        let canonical: Vec<Vec<Option<String>>> = name
+            .0
+            .iter()
+            .map(|rdn| {
+                rdn.0
+                    .iter()
+                    .map(|atv| value_as_string(&atv.value).map(|s| openssl_canon(&s)))
+                    .collect()
+            })
+            .collect();
+
+        // The canonical encoding is the RDN sets without the outer SEQUENCE
+        let mut encoded = Vec::new();
+
+        for (rdn, values) in name.0.iter().zip(&canonical) {
+            let atvs = rdn
+                .0
+                .iter()
+                .zip(values)
+                .map(|(atv, value)| match value {
+                    Some(value) => Ok(AttributeTypeAndValue {
+                        oid: atv.oid,
+                        value: AnyRef::new(Tag::Utf8String, value.as_bytes())?,
+                    }),
+                    None => Ok(*atv),
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            encoded.extend(RelativeDistinguishedName(atvs.try_into()?).to_vec()?);
+        }
+
+        let digest = Sha1::digest(&encoded);
+
+        Ok(u32::from_le_bytes([
+            digest[0], digest[1], digest[2], digest[3],
+        ]))
+    }
+}
+
+fn prepare_rdn(rdn: &RelativeDistinguishedName<'_>) -> Result<Vec<(AttributeType, Prepared)>> {
+    let mut prepared = rdn
+        .0
+        .iter()
+        .map(|atv| {
+            let value = match value_as_string(&atv.value) {
+                Some(s) => Prepared::String(prepare_string(&s)),
+                None => Prepared::Der(atv.value.to_vec()?),
+            };
+
+            Ok((atv.oid, value))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    prepared.sort();
+    Ok(prepared)
+}
+
+/// RFC 4518 string preparation for caseIgnoreMatch, without the normalize step
+fn prepare_string(s: &str) -> String {
+    let mut prepared = String::with_capacity(s.len());
+
+    for c in s.chars() {
+        match c {
+            // Mapped to nothing
+            '\u{00AD}'
+            | '\u{1806}'
+            | '\u{034F}'
+            | '\u{180B}'..='\u{180D}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{FFFC}'
+            | '\u{200B}'..='\u{200D}'
+            | '\u{2060}'
+            | '\u{FEFF}' => {}
+            '\u{0009}' | '\u{000A}'..='\u{000D}' | '\u{0085}' => prepared.push(' '),
+            c if c.is_control() => {}
+            c if c.is_whitespace() => prepared.push(' '),
+            c => prepared.extend(c.to_lowercase()),
+        }
+    }
+
+    prepared
+        .split(' ')
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Canonicalizes a string value like `asn1_string_canon` in OpenSSL: leading and trailing
+/// whitespace is removed, runs of whitespace are collapsed into one space and ASCII letters
+/// are lowercased
+fn openssl_canon(s: &str) -> String {
+    s.split(|c: char| c.is_ascii_whitespace() || c == '\u{000B}')
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use der::{asn1::AnyRef, Decode, Tag};
+    use x509_cert::name::RdnSequence;
+
+    use super::Name;
+    use crate::{context::Context, name::StringEncoding};
+
+    #[test]
+    fn test_openssl_hash() {
+        // `openssl x509 -subject_hash` of a certificate with the subject
+        // `/C=US/O=  Example   Org /CN=Foo Bar+UID=x1`
+        let der = [
+            0x30, 0x4c, 0x31, 0x0b, 0x30, 0x09, 0x06, 0x03, 0x55, 0x04, 0x06, 0x13, 0x02, 0x55,
+            0x53, 0x31, 0x19, 0x30, 0x17, 0x06, 0x03, 0x55, 0x04, 0x0a, 0x0c, 0x10, 0x20, 0x20,
+            0x45, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x20, 0x20, 0x20, 0x4f, 0x72, 0x67, 0x20,
+            0x31, 0x22, 0x30, 0x0e, 0x06, 0x03, 0x55, 0x04, 0x03, 0x0c, 0x07, 0x46, 0x6f, 0x6f,
+            0x20, 0x42, 0x61, 0x72, 0x30, 0x10, 0x06, 0x0a, 0x09, 0x92, 0x26, 0x89, 0x93, 0xf2,
+            0x2c, 0x64, 0x01, 0x01, 0x0c, 0x02, 0x78, 0x31,
+        ];
+        let name = RdnSequence::from_der(&der).unwrap();
+
+        assert_eq!(Name::openssl_hash(&name).unwrap(), 0x37b578c8);
+
+        // Case and spaces don't change the hash
+        let context = Context::new();
+        let other = Name::parse_rfc4514_unchecked(&context, "CN=foo bar+UID=X1,O=example org,C=us")
+            .unwrap();
+        assert_eq!(Name::openssl_hash(&other).unwrap(), 0x37b578c8);
+    }
+
+    #[test]
+    fn test_eq_normalized() {
+        let context = Context::new();
+
+        let a = Name::parse_rfc4514(&context, "CN=Foo  Bar,O=Example,C=US").unwrap();
+        let b = Name::parse_rfc4514_unchecked(&context, "CN=\\ foo bar\\ ,O=EXAMPLE,C=us").unwrap();
+        assert!(Name::eq_normalized(&a, &b));
+
+        let c = Name::parse_rfc4514(&context, "CN=Foo Baz,O=Example,C=US").unwrap();
+        assert!(!Name::eq_normalized(&a, &c));
+
+        // The string type doesn't matter
+        let printable = Name::from_pairs_with_encodings(
+            &context,
+            &[(const_oid::db::rfc4519::CN, "Foo", StringEncoding::Printable)],
+        )
+        .unwrap();
+        let bmp = Name::from_pairs_with_encodings(
+            &context,
+            &[(const_oid::db::rfc4519::CN, "FOO", StringEncoding::Bmp)],
+        )
+        .unwrap();
+        assert!(Name::eq_normalized(&printable, &bmp));
+
+        // Neither does the order of a multi-valued RDN, but the RDN order does
+        assert!(Name::eq_normalized(
+            &Name::parse_rfc4514(&context, "CN=foo+UID=1").unwrap(),
+            &Name::parse_rfc4514(&context, "UID=1+CN=FOO").unwrap()
+        ));
+        assert!(!Name::eq_normalized(
+            &Name::parse_rfc4514(&context, "CN=a,O=b").unwrap(),
+            &Name::parse_rfc4514(&context, "O=b,CN=a").unwrap()
+        ));
+
+        // Non-string values are compared by their encoding
+        let octets = AnyRef::new(Tag::OctetString, b"Foo").unwrap();
+        let mut other = printable.clone();
+        other.0[0].0 = vec![x509_cert::attr::AttributeTypeAndValue {
+            oid: const_oid::db::rfc4519::CN,
+            value: octets,
+        }]
+        .try_into()
+        .unwrap();
+        assert!(!Name::eq_normalized(&printable, &other));
+    }
+}
@@ -1,5 +1,6 @@
 pub use crate::context::Context;
-pub use crate::extensions::Extensions;
+pub use crate::extensions::{DecodedExtension, ExtensionSet, Extensions};
+pub use crate::general_name::{GeneralName, GeneralSubtree};
 pub use crate::name::Name;
 pub use crate::pem::PemOperations;
 pub use crate::signer::{Signable, Signer};
@@ -1,4 +1,5 @@
 use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha384, Sha512};
 
 use spki::der::Decode;
 
@@ -7,6 +8,29 @@ use crate::{
     error::Result,
 };
 
+/// Methods for deriving a key identifier from the subject public key, see
+/// [RFC 5280 Section 4.2.1.2](https://www.rfc-editor.org/rfc/rfc5280#section-4.2.1.2) and
+/// [RFC 7093 Section 2](https://www.rfc-editor.org/rfc/rfc7093#section-2)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyIdentifierMethod {
+    /// SHA-1 of the subject public key, RFC 5280 method (1)
+    #[default]
+    Rfc5280Method1,
+    /// `0100` followed by the least significant 60 bits of the SHA-1, RFC 5280 method (2)
+    Rfc5280Method2,
+    /// Leftmost 160 bits of the SHA-256, RFC 7093 method (1)
+    Rfc7093Method1,
+    /// Leftmost 160 bits of the SHA-384, RFC 7093 method (2)
+    Rfc7093Method2,
+    /// Leftmost 160 bits of the SHA-512, RFC 7093 method (3)
+    Rfc7093Method3,
+    /// Full SHA-256 of the subject public key
+    Sha256,
+}
+
+/// Length of the truncated RFC 7093 identifiers, matching a SHA-1 digest
+const TRUNCATED_LEN: usize = 20;
+
 pub struct SubjectPublicKeyInfo;
 
 impl SubjectPublicKeyInfo {
@@ -20,11 +44,64 @@ impl SubjectPublicKeyInfo {
     }
 
     pub fn as_key_identifier(subjpubkey: spki::SubjectPublicKeyInfo) -> Vec<u8> {
+        Self::as_key_identifier_with(subjpubkey, KeyIdentifierMethod::default())
+    }
+
+    pub fn as_key_identifier_with(
+        subjpubkey: spki::SubjectPublicKeyInfo,
+        method: KeyIdentifierMethod,
+    ) -> Vec<u8> {
         let bytes = subjpubkey.subject_public_key;
 
-        let mut hasher = Sha1::new();
-        hasher.update(bytes);
+        match method {
+            KeyIdentifierMethod::Rfc5280Method1 => Sha1::digest(bytes).to_vec(),
+            KeyIdentifierMethod::Rfc5280Method2 => {
+                let digest = Sha1::digest(bytes);
+                let mut ident = digest[digest.len() - 8..].to_vec();
+                ident[0] = 0x40 | (ident[0] & 0x0f);
+                ident
+            }
+            KeyIdentifierMethod::Rfc7093Method1 => Sha256::digest(bytes)[..TRUNCATED_LEN].to_vec(),
+            KeyIdentifierMethod::Rfc7093Method2 => Sha384::digest(bytes)[..TRUNCATED_LEN].to_vec(),
+            KeyIdentifierMethod::Rfc7093Method3 => Sha512::digest(bytes)[..TRUNCATED_LEN].to_vec(),
+            KeyIdentifierMethod::Sha256 => Sha256::digest(bytes).to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KeyIdentifierMethod, SubjectPublicKeyInfo};
+
+    fn ident(method: KeyIdentifierMethod) -> Vec<u8> {
+        let spki = spki::SubjectPublicKeyInfo {
+            algorithm: crate::algorithm_identifier::ECDSA_WITH_SHA256,
+            subject_public_key: b"abc",
+        };
+
+        SubjectPublicKeyInfo::as_key_identifier_with(spki, method)
+    }
+
+    #[test]
+    fn test_key_identifier_methods() {
+        // SHA-1("abc") = a9993e364706816aba3e25717850c26c9cd0d89d
+        assert_eq!(
+            ident(KeyIdentifierMethod::Rfc5280Method1)[..2],
+            [0xa9, 0x99]
+        );
+        assert_eq!(
+            ident(KeyIdentifierMethod::Rfc5280Method2),
+            [0x48, 0x50, 0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d]
+        );
+
+        // SHA-256("abc") = ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad
+        let truncated = ident(KeyIdentifierMethod::Rfc7093Method1);
+        assert_eq!(truncated.len(), 20);
+        assert_eq!(truncated[..2], [0xba, 0x78]);
+        assert_eq!(truncated[19], 0xa3);
 
-        hasher.finalize().to_vec()
+        assert_eq!(ident(KeyIdentifierMethod::Rfc7093Method2).len(), 20);
+        assert_eq!(ident(KeyIdentifierMethod::Rfc7093Method3).len(), 20);
+        assert_eq!(ident(KeyIdentifierMethod::Sha256).len(), 32);
     }
 }
@@ -0,0 +1,51 @@
+//! Fixtures shared by the unit tests
+
+use der::asn1::{BitStringRef, UIntRef, UtcTime};
+use x509_cert::{
+    ext::Extension,
+    time::{Time, Validity},
+    Certificate, TbsCertificate,
+};
+
+use crate::{context::Context, name::Name};
+
+/// A self-issued `CN=example.com` V3 TBSCertificate with serial `0x42`, valid at the Unix
+/// epoch only, for a dummy EC key
+pub(crate) fn tbs_certificate<'a>(
+    context: &'a Context,
+    extensions: Option<Vec<Extension<'a>>>,
+) -> TbsCertificate<'a> {
+    let name = Name::from_pairs(context, &[("CN", "example.com")]).unwrap();
+    let time = Time::UtcTime(UtcTime::from_unix_duration(std::time::Duration::ZERO).unwrap());
+
+    TbsCertificate {
+        version: x509_cert::Version::V3,
+        serial_number: UIntRef::new(&[0x42]).unwrap(),
+        signature: crate::algorithm_identifier::ECDSA_WITH_SHA256,
+        issuer: name.clone(),
+        validity: Validity {
+            not_before: time,
+            not_after: time,
+        },
+        subject: name,
+        subject_public_key_info: spki::SubjectPublicKeyInfo {
+            algorithm: spki::AlgorithmIdentifier {
+                oid: const_oid::db::rfc5912::ID_EC_PUBLIC_KEY,
+                parameters: None,
+            },
+            subject_public_key: &[4, 1, 2, 3],
+        },
+        issuer_unique_id: None,
+        subject_unique_id: None,
+        extensions,
+    }
+}
+
+/// Wraps `tbs_certificate` with an empty signature
+pub(crate) fn certificate(tbs_certificate: TbsCertificate<'_>) -> Certificate<'_> {
+    Certificate {
+        tbs_certificate,
+        signature_algorithm: crate::algorithm_identifier::ECDSA_WITH_SHA256,
+        signature: BitStringRef::from_bytes(&[]).unwrap(),
+    }
+}
@@ -1,26 +1,53 @@
-use std::{ops::Range, time::Duration};
+use std::{ops::Range, str::FromStr};
 
-use chrono::{DateTime, Datelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, SubsecRound, Timelike, Utc};
 use der::asn1::GeneralizedTime;
 use spki::der::asn1::UtcTime;
 
 use crate::error::Error;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Validity {
     pub not_before: DateTime<Utc>,
     pub not_after: DateTime<Utc>,
 }
 
+/// The GeneralizedTime `99991231235959Z` of RFC 5280 Section 4.1.2.5, for certificates without
+/// a well-defined expiration date such as IEEE 802.1AR IDevIDs
+pub const NO_EXPIRATION: DateTime<Utc> = match NaiveDate::from_ymd_opt(9999, 12, 31) {
+    Some(date) => match date.and_hms_opt(23, 59, 59) {
+        Some(time) => time.and_utc(),
+        None => panic!(),
+    },
+    None => panic!(),
+};
+
+/// Encodes times through 2049 as UTCTime and later ones as GeneralizedTime, as required by
+/// RFC 5280 Section 4.1.2.5. Fractional seconds are dropped.
 fn chrono_as_time(ch: &DateTime<Utc>) -> Option<x509_cert::time::Time> {
-    let offset = ch.timestamp_millis().try_into().ok()?;
-    let duration = Duration::from_millis(offset);
+    let datetime = der::DateTime::new(
+        ch.year().try_into().ok()?,
+        ch.month().try_into().ok()?,
+        ch.day().try_into().ok()?,
+        ch.hour().try_into().ok()?,
+        ch.minute().try_into().ok()?,
+        ch.second().try_into().ok()?,
+    )
+    .ok()?;
 
     if ch.year() < 2050 {
-        UtcTime::from_unix_duration(duration).ok().map(|v| v.into())
+        UtcTime::from_date_time(datetime).ok().map(|v| v.into())
+    } else {
+        Some(GeneralizedTime::from_date_time(datetime).into())
+    }
+}
+
+/// der's `DateTime` starts at the Unix epoch, so UTCTimes of 1950 to 1969 can't be built
+fn check_representable(ch: &DateTime<Utc>) -> Result<(), Error> {
+    if ch.year() < 1970 {
+        Err(Error::UnsupportedTime(*ch))
     } else {
-        GeneralizedTime::from_unix_duration(duration)
-            .ok()
-            .map(|v| v.into())
+        Ok(())
     }
 }
 
@@ -28,6 +55,9 @@ impl TryFrom<&Validity> for x509_cert::time::Validity {
     type Error = crate::error::Error;
 
     fn try_from(this: &Validity) -> Result<Self, Self::Error> {
+        check_representable(&this.not_before)?;
+        check_representable(&this.not_after)?;
+
         Ok(x509_cert::time::Validity {
             not_before: chrono_as_time(&this.not_before).ok_or(Error::FailedBuildingNotBefore)?,
             not_after: chrono_as_time(&this.not_after).ok_or(Error::FailedBuildingNotAfter)?,
@@ -35,6 +65,24 @@ impl TryFrom<&Validity> for x509_cert::time::Validity {
     }
 }
 
+fn time_as_chrono(time: &x509_cert::time::Time) -> Result<DateTime<Utc>, Error> {
+    i64::try_from(time.to_unix_duration().as_secs())
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .ok_or(Error::UnrepresentableTime(*time))
+}
+
+impl TryFrom<&x509_cert::time::Validity> for Validity {
+    type Error = crate::error::Error;
+
+    fn try_from(this: &x509_cert::time::Validity) -> Result<Self, Self::Error> {
+        Ok(Validity {
+            not_before: time_as_chrono(&this.not_before)?,
+            not_after: time_as_chrono(&this.not_after)?,
+        })
+    }
+}
+
 impl From<Range<DateTime<Utc>>> for Validity {
     fn from(this: Range<DateTime<Utc>>) -> Self {
         Validity {
@@ -44,12 +92,271 @@ impl From<Range<DateTime<Utc>>> for Validity {
     }
 }
 
+impl Validity {
+    /// Valid from `not_before` without a well-defined expiration date
+    pub fn no_expiration(not_before: DateTime<Utc>) -> Self {
+        Validity {
+            not_before,
+            not_after: NO_EXPIRATION,
+        }
+    }
+
+    /// The validity period as defined by RFC 5280, which includes both `not_before` and
+    /// `not_after`, so one second longer than their difference
+    pub fn lifetime(&self) -> chrono::Duration {
+        self.not_after - self.not_before + chrono::Duration::seconds(1)
+    }
+
+    /// Whether `at` falls within the validity, both ends included
+    pub fn is_valid_at(&self, at: DateTime<Utc>) -> bool {
+        self.not_before <= at && at <= self.not_after
+    }
+
+    /// Time left of the [`lifetime`](Self::lifetime), one second at `not_after` itself and zero
+    /// once expired
+    pub fn remaining_at(&self, at: DateTime<Utc>) -> chrono::Duration {
+        (self.not_before + self.lifetime() - at).max(chrono::Duration::zero())
+    }
+
+    /// Share of the validity that has passed at `at`, from 0 before `not_before` to 100 after
+    /// `not_after`. Renewal is commonly due once two thirds have elapsed.
+    pub fn percent_elapsed_at(&self, at: DateTime<Utc>) -> f64 {
+        let total = self.lifetime().num_seconds();
+        let elapsed = (at - self.not_before).num_seconds();
+
+        if total <= 0 {
+            return if elapsed < 0 { 0.0 } else { 100.0 };
+        }
+
+        (elapsed as f64 / total as f64 * 100.0).clamp(0.0, 100.0)
+    }
+
+    /// Checks `not_after` isn't earlier than `not_before` and the lifetime is within the limit
+    /// of `profile`
+    pub fn validate(&self, profile: LifetimeProfile) -> Result<(), Error> {
+        if self.not_after < self.not_before {
+            return Err(Error::NotAfterBeforeNotBefore(
+                self.not_before,
+                self.not_after,
+            ));
+        }
+
+        match profile.max_lifetime() {
+            Some(max) if self.lifetime() > max => Err(Error::LifetimeExceedsMaximum(
+                self.lifetime().num_seconds(),
+                max.num_seconds(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the validity is nested within the validity of its issuer
+    pub fn check_within(&self, issuer: &Validity) -> Result<(), Error> {
+        if self.not_before < issuer.not_before {
+            Err(Error::NotBeforeBeforeIssuer(
+                self.not_before,
+                issuer.not_before,
+            ))
+        } else if self.not_after > issuer.not_after {
+            Err(Error::NotAfterAfterIssuer(self.not_after, issuer.not_after))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Shortens the validity to fit within the validity of its issuer
+    pub fn clamp_to(&self, issuer: &Validity) -> Validity {
+        Validity {
+            not_before: self.not_before.max(issuer.not_before),
+            not_after: self.not_after.min(issuer.not_after),
+        }
+    }
+}
+
+/// Maximum lifetimes imposed on certificates of a kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifetimeProfile {
+    Unrestricted,
+    /// TLS server certificates, 398 days per the CA/Browser Forum Baseline Requirements
+    TlsServer,
+    /// S/MIME certificates, 825 days per the CA/Browser Forum S/MIME Baseline Requirements
+    SMime,
+    Custom(chrono::Duration),
+}
+
+impl LifetimeProfile {
+    pub fn max_lifetime(&self) -> Option<chrono::Duration> {
+        match self {
+            LifetimeProfile::Unrestricted => None,
+            LifetimeProfile::TlsServer => Some(chrono::Duration::days(398)),
+            LifetimeProfile::SMime => Some(chrono::Duration::days(825)),
+            LifetimeProfile::Custom(max) => Some(*max),
+        }
+    }
+}
+
+impl FromStr for LifetimeProfile {
+    type Err = Error;
+
+    /// Parses `unrestricted`, `tls-server`, `smime` or a duration such as `90d`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "unrestricted" => Ok(LifetimeProfile::Unrestricted),
+            "tls-server" => Ok(LifetimeProfile::TlsServer),
+            "smime" => Ok(LifetimeProfile::SMime),
+            _ => parse_duration(s).map(LifetimeProfile::Custom),
+        }
+    }
+}
+
+/// One end of a validity period, parsed from an RFC 3339 timestamp, a `YYYY-MM-DD` date,
+/// `now[±<duration>]`, `today[±<duration>]`, a bare `±<duration>` or `never` for
+/// [`NO_EXPIRATION`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeSpec {
+    Absolute(DateTime<Utc>),
+    /// Offset from the current time
+    Now(chrono::Duration),
+    /// Offset from the start of the current day, in UTC
+    Today(chrono::Duration),
+    /// Offset from the other end of the range: `not_before` is relative to now, `not_after` to
+    /// `not_before`
+    Relative(chrono::Duration),
+}
+
+impl TimeSpec {
+    fn resolve(&self, base: DateTime<Utc>, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            TimeSpec::Absolute(time) => *time,
+            TimeSpec::Now(offset) => now + *offset,
+            TimeSpec::Today(offset) => {
+                now.date_naive().and_time(Default::default()).and_utc() + *offset
+            }
+            TimeSpec::Relative(offset) => base + *offset,
+        }
+    }
+}
+
+impl FromStr for TimeSpec {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let offset = |rest: &str| {
+            if rest.is_empty() {
+                Ok(chrono::Duration::zero())
+            } else if rest.starts_with(['+', '-']) {
+                parse_duration(rest)
+            } else {
+                Err(Error::InvalidTimeSpec(s.to_string()))
+            }
+        };
+
+        if s == "never" {
+            Ok(TimeSpec::Absolute(NO_EXPIRATION))
+        } else if let Some(rest) = s.strip_prefix("now") {
+            Ok(TimeSpec::Now(offset(rest)?))
+        } else if let Some(rest) = s.strip_prefix("today") {
+            Ok(TimeSpec::Today(offset(rest)?))
+        } else if s.starts_with(['+', '-']) {
+            Ok(TimeSpec::Relative(parse_duration(s)?))
+        } else if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+            Ok(TimeSpec::Absolute(time.with_timezone(&Utc)))
+        } else if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            Ok(TimeSpec::Absolute(
+                date.and_time(Default::default()).and_utc(),
+            ))
+        } else {
+            Err(Error::InvalidTimeSpec(s.to_string()))
+        }
+    }
+}
+
+/// Parses a signed duration made of `<number><unit>` parts, e.g. `90d`, `-5m` or `+1d12h`.
+/// Units are `s`, `m`, `h`, `d` and `w`.
+pub fn parse_duration(s: &str) -> Result<chrono::Duration, Error> {
+    let invalid = || Error::InvalidTimeSpec(s.to_string());
+
+    let (negative, mut rest) = match s.trim() {
+        r if r.starts_with('-') => (true, &r[1..]),
+        r => (false, r.strip_prefix('+').unwrap_or(r)),
+    };
+
+    if rest.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut seconds: i64 = 0;
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(invalid)?;
+        let value: i64 = rest[..digits].parse().map_err(|_| invalid())?;
+        let unit = match rest[digits..].chars().next() {
+            Some('s') => 1,
+            Some('m') => 60,
+            Some('h') => 60 * 60,
+            Some('d') => 24 * 60 * 60,
+            Some('w') => 7 * 24 * 60 * 60,
+            _ => return Err(invalid()),
+        };
+
+        seconds = value
+            .checked_mul(unit)
+            .and_then(|v| seconds.checked_add(v))
+            .ok_or_else(invalid)?;
+        rest = &rest[digits + 1..];
+    }
+
+    let duration = chrono::Duration::try_seconds(seconds).ok_or_else(invalid)?;
+
+    Ok(if negative { -duration } else { duration })
+}
+
+/// A validity period that is resolved against the current time when signing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValiditySpec {
+    pub not_before: TimeSpec,
+    pub not_after: TimeSpec,
+    /// Subtracted from `not_before` to tolerate relying parties with a clock running behind.
+    /// Relative `not_after` values are not affected.
+    pub backdate: chrono::Duration,
+}
+
+impl ValiditySpec {
+    /// Valid from now for `days`
+    pub fn days(days: u32) -> Self {
+        ValiditySpec {
+            not_before: TimeSpec::Now(chrono::Duration::zero()),
+            not_after: TimeSpec::Relative(chrono::Duration::days(days.into())),
+            backdate: chrono::Duration::zero(),
+        }
+    }
+
+    /// Resolves the spec against `now`, truncating both ends to whole seconds
+    pub fn resolve(&self, now: DateTime<Utc>) -> Validity {
+        let not_before = self.not_before.resolve(now, now);
+        let not_after = self.not_after.resolve(not_before, now);
+
+        Validity {
+            not_before: (not_before - self.backdate).trunc_subsecs(0),
+            not_after: not_after.trunc_subsecs(0),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use chrono::TimeZone;
+    use chrono::{Duration, TimeZone};
+    use der::Encode;
     use x509_cert::time::Time;
 
-    use super::chrono_as_time;
+    use super::{
+        chrono_as_time, parse_duration, LifetimeProfile, TimeSpec, Validity, ValiditySpec,
+        NO_EXPIRATION,
+    };
+    use crate::error::Error;
 
     #[test]
     fn test_chrono_to_utctime() {
@@ -68,4 +375,225 @@ mod tests {
 
         assert!(matches!(chrono_as_time(&time), Some(Time::GeneralTime(_))));
     }
+
+    #[test]
+    fn test_2050_boundary() {
+        let encode = |time| chrono_as_time(&time).unwrap().to_vec().unwrap();
+
+        let last_utc = chrono::Utc
+            .with_ymd_and_hms(2049, 12, 31, 23, 59, 59)
+            .unwrap();
+        assert_eq!(encode(last_utc), b"\x17\x0d491231235959Z");
+        assert_eq!(
+            encode(last_utc + Duration::milliseconds(999)),
+            b"\x17\x0d491231235959Z"
+        );
+        assert_eq!(
+            encode(last_utc + Duration::seconds(1)),
+            b"\x18\x0f20500101000000Z"
+        );
+        assert_eq!(encode(NO_EXPIRATION), b"\x18\x0f99991231235959Z");
+
+        let validity = Validity::no_expiration(last_utc);
+        let validity: x509_cert::time::Validity = (&validity).try_into().unwrap();
+        assert!(matches!(validity.not_before, Time::UtcTime(_)));
+        assert!(matches!(validity.not_after, Time::GeneralTime(_)));
+        assert_eq!(
+            "never".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Absolute(NO_EXPIRATION)
+        );
+    }
+
+    #[test]
+    fn test_pre_epoch() {
+        let time = chrono::Utc
+            .with_ymd_and_hms(1969, 12, 31, 23, 59, 59)
+            .unwrap();
+        let validity: Validity = (time..NO_EXPIRATION).into();
+
+        assert!(matches!(
+            x509_cert::time::Validity::try_from(&validity),
+            Err(Error::UnsupportedTime(t)) if t == time
+        ));
+
+        let epoch = chrono::Utc.with_ymd_and_hms(1970, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            chrono_as_time(&epoch).unwrap().to_vec().unwrap(),
+            b"\x17\x0d700101000000Z"
+        );
+    }
+
+    #[test]
+    fn test_time_spec() {
+        assert_eq!(
+            "now".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Now(Duration::zero())
+        );
+        assert_eq!(
+            "now-5m".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Now(Duration::minutes(-5))
+        );
+        assert_eq!(
+            "today".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Today(Duration::zero())
+        );
+        assert_eq!(
+            "+397d".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Relative(Duration::days(397))
+        );
+        assert_eq!(
+            "2025-01-01T12:00:00+02:00".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Absolute(chrono::Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap())
+        );
+        assert_eq!(
+            "2025-01-01".parse::<TimeSpec>().unwrap(),
+            TimeSpec::Absolute(chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap())
+        );
+
+        assert_eq!(
+            parse_duration("1w1d12h30m15s").unwrap(),
+            Duration::seconds(8 * 86400 + 12 * 3600 + 30 * 60 + 15)
+        );
+        assert!(parse_duration("90").is_err());
+        assert!(parse_duration("5y").is_err());
+        assert!(parse_duration("-").is_err());
+        assert!("tomorrow".parse::<TimeSpec>().is_err());
+        assert!("now+".parse::<TimeSpec>().is_err());
+        assert!("now5d".parse::<TimeSpec>().is_err());
+    }
+
+    #[test]
+    fn test_validity_spec() {
+        let now = chrono::Utc
+            .with_ymd_and_hms(2025, 3, 10, 15, 30, 0)
+            .unwrap()
+            + Duration::milliseconds(250);
+
+        let validity = ValiditySpec {
+            backdate: Duration::minutes(5),
+            ..ValiditySpec::days(90)
+        }
+        .resolve(now);
+        assert_eq!(
+            validity.not_before,
+            chrono::Utc
+                .with_ymd_and_hms(2025, 3, 10, 15, 25, 0)
+                .unwrap()
+        );
+        assert_eq!(
+            validity.not_after,
+            chrono::Utc.with_ymd_and_hms(2025, 6, 8, 15, 30, 0).unwrap()
+        );
+
+        let validity = ValiditySpec {
+            not_before: "today".parse().unwrap(),
+            not_after: "+397d".parse().unwrap(),
+            backdate: Duration::zero(),
+        }
+        .resolve(now);
+        assert_eq!(
+            validity.not_before,
+            chrono::Utc.with_ymd_and_hms(2025, 3, 10, 0, 0, 0).unwrap()
+        );
+        assert_eq!(
+            validity.not_after,
+            chrono::Utc.with_ymd_and_hms(2026, 4, 11, 0, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_validity_checks() {
+        let start = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let validity: Validity = (start..start + Duration::days(398)).into();
+
+        // The inclusive end makes a 398 day difference one second too long
+        assert!(matches!(
+            validity.validate(LifetimeProfile::TlsServer),
+            Err(Error::LifetimeExceedsMaximum(_, _))
+        ));
+        let validity: Validity = (start..start + Duration::days(398) - Duration::seconds(1)).into();
+        assert!(validity.validate(LifetimeProfile::TlsServer).is_ok());
+        assert!(validity.validate("397d".parse().unwrap()).is_err());
+
+        let backwards: Validity = (start..start - Duration::seconds(1)).into();
+        assert!(matches!(
+            backwards.validate(LifetimeProfile::Unrestricted),
+            Err(Error::NotAfterBeforeNotBefore(_, _))
+        ));
+
+        let issuer: Validity = (start + Duration::days(1)..start + Duration::days(365)).into();
+        assert!(matches!(
+            validity.check_within(&issuer),
+            Err(Error::NotBeforeBeforeIssuer(_, _))
+        ));
+
+        let clamped = validity.clamp_to(&issuer);
+        assert_eq!(clamped, issuer);
+        assert!(clamped.check_within(&issuer).is_ok());
+
+        let issuer: Validity = (start..start + Duration::days(365)).into();
+        assert!(matches!(
+            validity.check_within(&issuer),
+            Err(Error::NotAfterAfterIssuer(_, _))
+        ));
+    }
+
+    #[test]
+    fn test_from_x509() {
+        let start = chrono::Utc.with_ymd_and_hms(2049, 6, 1, 12, 0, 0).unwrap();
+        let validity: Validity = (start..start + Duration::days(365)).into();
+        let x509: x509_cert::time::Validity = (&validity).try_into().unwrap();
+
+        assert!(matches!(x509.not_after, Time::GeneralTime(_)));
+        assert_eq!(Validity::try_from(&x509).unwrap(), validity);
+
+        let validity = Validity::no_expiration(start);
+        let x509: x509_cert::time::Validity = (&validity).try_into().unwrap();
+        assert_eq!(Validity::try_from(&x509).unwrap().not_after, NO_EXPIRATION);
+    }
+
+    #[test]
+    fn test_lifetime_helpers() {
+        let start = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let validity: Validity = (start..start + Duration::days(90) - Duration::seconds(1)).into();
+        assert_eq!(validity.lifetime(), Duration::days(90));
+
+        assert!(!validity.is_valid_at(start - Duration::seconds(1)));
+        assert!(validity.is_valid_at(start));
+        assert!(validity.is_valid_at(validity.not_after));
+        assert!(!validity.is_valid_at(validity.not_after + Duration::seconds(1)));
+
+        assert_eq!(
+            validity.remaining_at(start + Duration::days(30)),
+            Duration::days(60)
+        );
+        assert_eq!(
+            validity.remaining_at(validity.not_after),
+            Duration::seconds(1)
+        );
+        assert_eq!(
+            validity.remaining_at(validity.not_after + Duration::seconds(1)),
+            Duration::zero()
+        );
+        assert_eq!(
+            validity.remaining_at(start + Duration::days(91)),
+            Duration::zero()
+        );
+
+        assert_eq!(validity.percent_elapsed_at(start - Duration::days(1)), 0.0);
+        assert_eq!(
+            validity.percent_elapsed_at(start + Duration::days(45)),
+            50.0
+        );
+        assert!(validity.percent_elapsed_at(start + Duration::days(60)) > 66.6);
+        assert!(validity.percent_elapsed_at(validity.not_after) < 100.0);
+        assert_eq!(
+            validity.percent_elapsed_at(validity.not_after + Duration::seconds(1)),
+            100.0
+        );
+        assert_eq!(
+            validity.percent_elapsed_at(start + Duration::days(91)),
+            100.0
+        );
+    }
 }
@@ -1,32 +1,180 @@
-use color_eyre::Result;
+use color_eyre::{eyre::eyre, Result};
 use x509_util::{
-    prelude::{Context, Extensions, PemOperations},
+    consistency,
+    extensions::{AuthorityInfoAccess, SubjectInfoAccess},
+    prelude::{Context, ExtensionSet, Extensions, GeneralName, PemOperations},
     signer::{Signable, Signer},
     validity::Validity,
     x509_cert::TbsCertificate,
 };
 
-use crate::cli::SigningArgs;
+use crate::cli::{QcSpec, SigningArgs};
 
 pub async fn self_sign<S: Signer + Send + Sync>(signer: S, args: &SigningArgs) -> Result<()> {
     let context = Context::new();
 
     let name = args.name.to_x509_name(&context)?;
-    let validity: Validity = (args.validity.not_before..args.validity.not_after).into();
+    let validity: Validity = args.validity.to_spec().resolve(chrono::Utc::now());
+    validity.validate(args.validity.max_lifetime)?;
     let spki = signer.subject_public_key_info(&context).await?;
 
-    let mut extensions = vec![
-        Extensions::basic_constraints(&context, args.ca, true, args.ca_pathlen)?,
-        Extensions::authority_key_identifier(&context, false, spki)?,
-        Extensions::subject_key_identifier(&context, false, spki)?,
-    ];
+    if let Some(profile) = args.profile {
+        profile.check_san(&args.san).map_err(|e| eyre!(e))?;
+    }
+
+    let key_id_method = args.key_id_method.map(Into::into).unwrap_or_default();
+
+    let mut extensions = ExtensionSet::try_from(vec![
+        Extensions::basic_constraints(&context, true, args.ca, args.ca_pathlen)?,
+        Extensions::authority_key_identifier_with_method(&context, false, spki, key_id_method)?,
+        Extensions::subject_key_identifier_with_method(&context, false, spki, key_id_method)?,
+    ])?;
+
+    let usages = args
+        .usages
+        .clone()
+        .or_else(|| args.profile.map(|p| p.usages(spki.algorithm.oid)));
 
-    if let Some(ref usages) = args.usages {
+    if let Some(ref usages) = usages {
         let usages: Vec<_> = usages.iter().map(Into::into).collect();
 
         let extension = Extensions::key_usage(&context, true, &usages)?;
 
-        extensions.push(extension);
+        extensions.push(extension)?;
+    }
+
+    let ext_usages = args
+        .ext_usages
+        .clone()
+        .or_else(|| args.profile.map(|p| p.ext_usages()));
+
+    if let Some(ref ext_usages) = ext_usages {
+        let purposes: Vec<_> = ext_usages.iter().map(Into::into).collect();
+
+        let extension = Extensions::extended_key_usage(&context, false, &purposes)?;
+
+        extensions.push(extension)?;
+    }
+
+    if !args.san.is_empty() {
+        let names = args
+            .san
+            .iter()
+            .map(|san| san.to_general_name(&context))
+            .collect::<Result<Vec<_>>>()?;
+
+        let extension = Extensions::subject_alt_name(&context, &name, &names)?;
+
+        extensions.push(extension)?;
+    }
+
+    if !args.permitted.is_empty() || !args.excluded.is_empty() {
+        let permitted = args
+            .permitted
+            .iter()
+            .map(|subtree| subtree.to_general_subtree(&context))
+            .collect::<Result<Vec<_>>>()?;
+        let excluded = args
+            .excluded
+            .iter()
+            .map(|subtree| subtree.to_general_subtree(&context))
+            .collect::<Result<Vec<_>>>()?;
+
+        let extension = Extensions::name_constraints(&context, true, &permitted, &excluded)?;
+
+        extensions.push(extension)?;
+    }
+
+    if !args.policy.is_empty() {
+        let policies: Vec<_> = args
+            .policy
+            .iter()
+            .map(|p| p.to_certificate_policy())
+            .collect();
+
+        let extension = Extensions::certificate_policies(&context, false, &policies)?;
+
+        extensions.push(extension)?;
+    }
+
+    if !args.crl_url.is_empty() {
+        let names: Vec<_> = args.crl_url.iter().map(|u| GeneralName::Uri(u)).collect();
+
+        let extension = Extensions::crl_distribution_points(&context, false, &names)?;
+
+        extensions.push(extension)?;
+    }
+
+    let authority_info_access: Vec<_> = args
+        .ocsp_url
+        .iter()
+        .map(|u| AuthorityInfoAccess::Ocsp(GeneralName::Uri(u)))
+        .chain(
+            args.ca_issuers_url
+                .iter()
+                .map(|u| AuthorityInfoAccess::CaIssuers(GeneralName::Uri(u))),
+        )
+        .collect();
+
+    if !authority_info_access.is_empty() {
+        let extension = Extensions::authority_info_access(&context, &authority_info_access)?;
+
+        extensions.push(extension)?;
+    }
+
+    let subject_info_access: Vec<_> = args
+        .ca_repository_url
+        .iter()
+        .map(|u| SubjectInfoAccess::CaRepository(GeneralName::Uri(u)))
+        .chain(
+            args.timestamping_url
+                .iter()
+                .map(|u| SubjectInfoAccess::TimeStamping(GeneralName::Uri(u))),
+        )
+        .collect();
+
+    if !subject_info_access.is_empty() {
+        let extension = Extensions::subject_info_access(&context, &subject_info_access)?;
+
+        extensions.push(extension)?;
+    }
+
+    if args.ocsp_no_check {
+        extensions.push(Extensions::ocsp_no_check(&context, false)?)?;
+    }
+
+    if let Some(ref tls_features) = args.tls_features {
+        let features: Vec<_> = tls_features.iter().map(Into::into).collect();
+
+        let extension = Extensions::tls_feature(&context, false, &features)?;
+
+        extensions.push(extension)?;
+    }
+
+    if !args.qc_statement.is_empty() {
+        let statements = QcSpec::to_qc_statements(&args.qc_statement);
+
+        let extension = Extensions::qc_statements(&context, false, &statements)?;
+
+        extensions.push(extension)?;
+    }
+
+    if let Some(ref template) = args.template {
+        extensions.push(Extensions::certificate_template(
+            &context,
+            false,
+            &template.0,
+        )?)?;
+    }
+
+    if let Some(ref sid) = args.sid {
+        extensions.push(Extensions::ntds_ca_security(&context, false, sid)?)?;
+    }
+
+    for raw in args.extension.iter() {
+        let extension = Extensions::raw(&context, raw.oid, raw.critical, &raw.value)?;
+
+        extensions.push(extension)?;
     }
 
     let tbs_certificate = TbsCertificate {
@@ -37,12 +185,22 @@ pub async fn self_sign<S: Signer + Send + Sync>(signer: S, args: &SigningArgs) -
         validity: (&validity).try_into()?,
         subject: name,
         subject_public_key_info: spki,
-        extensions: Some(extensions),
+        extensions: extensions.into_extensions(),
 
         issuer_unique_id: None,
         subject_unique_id: None,
     };
 
+    let issues = if args.allow_inconsistent {
+        consistency::check(&context, &tbs_certificate)
+    } else {
+        consistency::ensure_consistent(&context, &tbs_certificate)?
+    };
+
+    for issue in issues {
+        tracing::warn!(%issue, "inconsistent extensions");
+    }
+
     let certificate = tbs_certificate.sign(&context, &signer).await?;
 
     if let Some(ref output_path) = args.output {
@@ -1,21 +1,35 @@
 use std::path::PathBuf;
 
-use chrono::{DateTime, Utc};
 use clap::{Args, Parser, Subcommand, ValueHint};
+use x509_util::validity::{parse_duration, LifetimeProfile, TimeSpec, ValiditySpec};
 
 use crate::name::OwnedName;
 
+mod constraints;
 #[cfg(feature = "ecdsa")]
 mod ecdsa;
+mod extension;
+mod key_id;
+mod microsoft;
+mod policy;
+mod qc;
 #[cfg(feature = "rsa")]
 mod rsa;
+mod san;
 mod usage;
 
+pub use self::constraints::NameSubtree;
 #[cfg(feature = "ecdsa")]
 pub use self::ecdsa::EcdsaCurve;
+pub use self::extension::RawExtension;
+pub use self::key_id::KeyIdMethod;
+pub use self::microsoft::{Profile, TemplateSpec};
+pub use self::policy::PolicySpec;
+pub use self::qc::QcSpec;
 #[cfg(feature = "rsa")]
 pub use self::rsa::{RSAKeySize, RSASigningMode};
-pub use self::usage::KeyUsage;
+pub use self::san::SubjectAltName;
+pub use self::usage::{ExtKeyUsage, KeyUsage};
 
 #[derive(Debug, Parser)]
 #[clap(version)]
@@ -36,14 +50,65 @@ pub struct KeySelector {
 }
 
 #[derive(Debug, Args)]
+#[clap(group(
+    clap::ArgGroup::new("end").required(true)
+))]
 pub struct Validity {
-    #[clap(long, value_hint = ValueHint::Other)]
-    pub not_before: DateTime<Utc>,
-    #[clap(long, value_hint = ValueHint::Other)]
-    pub not_after: DateTime<Utc>,
+    #[clap(
+        long,
+        default_value = "now",
+        allow_hyphen_values = true,
+        value_hint = ValueHint::Other,
+        help = "RFC 3339 time, YYYY-MM-DD, now[±<duration>], today[±<duration>] or ±<duration> from now"
+    )]
+    pub not_before: TimeSpec,
+    #[clap(
+        long,
+        group = "end",
+        allow_hyphen_values = true,
+        value_hint = ValueHint::Other,
+        help = "Same syntax as --not-before, ±<duration> is relative to --not-before, never for 99991231235959Z"
+    )]
+    pub not_after: Option<TimeSpec>,
+    #[clap(
+        long,
+        group = "end",
+        help = "Valid for the given number of days after --not-before"
+    )]
+    pub days: Option<u32>,
+    #[clap(
+        long,
+        value_parser = parse_duration,
+        value_hint = ValueHint::Other,
+        help = "Move not before back to tolerate clock skew, e.g. 5m"
+    )]
+    pub backdate: Option<chrono::Duration>,
+    #[clap(
+        long,
+        default_value = "unrestricted",
+        value_hint = ValueHint::Other,
+        help = "Refuse validities longer than tls-server (398 days), smime (825 days) or a <duration>"
+    )]
+    pub max_lifetime: LifetimeProfile,
+}
+
+impl Validity {
+    pub fn to_spec(&self) -> ValiditySpec {
+        // clap requires one of --not-after or --days
+        let not_after = self
+            .not_after
+            .unwrap_or_else(|| ValiditySpec::days(self.days.unwrap_or_default()).not_after);
+
+        ValiditySpec {
+            not_before: self.not_before,
+            not_after,
+            backdate: self.backdate.unwrap_or_else(chrono::Duration::zero),
+        }
+    }
 }
 
 #[derive(Debug, Subcommand)]
+#[allow(clippy::large_enum_variant)] // Parsed exactly once, boxing the arguments buys nothing
 pub enum Commands {
     Generate,
     SelfSign {
@@ -60,6 +125,21 @@ pub enum HashMode {
     SHA512,
 }
 
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum TlsFeature {
+    StatusRequest,
+    StatusRequestV2,
+}
+
+impl From<&TlsFeature> for x509_util::extensions::TlsFeature {
+    fn from(feature: &TlsFeature) -> Self {
+        match feature {
+            TlsFeature::StatusRequest => x509_util::extensions::TlsFeature::StatusRequest,
+            TlsFeature::StatusRequestV2 => x509_util::extensions::TlsFeature::StatusRequestV2,
+        }
+    }
+}
+
 #[derive(Debug, Args)]
 pub struct SigningArgs {
     #[clap(short, long, value_hint = ValueHint::FilePath)]
@@ -70,10 +150,30 @@ pub struct SigningArgs {
     pub validity: Validity,
     #[clap(short, long, value_hint = ValueHint::Other, help = "Subject/Issuer in the form of C=US,ST=CA,L=\"San Francisco\"")]
     pub name: OwnedName,
+    #[clap(
+        long,
+        value_enum,
+        help = "Method used to derive the subject and authority key identifiers"
+    )]
+    pub key_id_method: Option<KeyIdMethod>,
     #[clap(long)]
     pub ca: bool,
     #[clap(long, value_hint = ValueHint::Other)]
     pub ca_pathlen: Option<u8>,
+    #[clap(
+        long,
+        requires = "ca",
+        value_hint = ValueHint::Other,
+        help = "X509v3 NameConstraints permitted subtree, one of dns:, ip:<addr>/<prefix>, email:, uri: or dirname:"
+    )]
+    pub permitted: Vec<NameSubtree>,
+    #[clap(
+        long,
+        requires = "ca",
+        value_hint = ValueHint::Other,
+        help = "X509v3 NameConstraints excluded subtree, same syntax as --permitted"
+    )]
+    pub excluded: Vec<NameSubtree>,
     #[clap(
         long,
         value_enum,
@@ -81,6 +181,82 @@ pub struct SigningArgs {
         help = "X509v3 KeyUsage extension values"
     )]
     pub usages: Option<Vec<KeyUsage>>,
+    #[clap(
+        long,
+        value_delimiter = ',',
+        value_hint = ValueHint::Other,
+        help = "X509v3 ExtendedKeyUsage extension values, one of server-auth, client-auth, code-signing, email-protection, time-stamping, ocsp-signing, smartcard-logon, kdc-auth or a dotted OID"
+    )]
+    pub ext_usages: Option<Vec<ExtKeyUsage>>,
+    #[clap(
+        long,
+        value_hint = ValueHint::Other,
+        help = "X509v3 SubjectAltName entry, one of dns:, ip:, email:, uri:, othername:<oid>;UTF8:, upn: or dirname:"
+    )]
+    pub san: Vec<SubjectAltName>,
+    #[clap(
+        long,
+        value_hint = ValueHint::Other,
+        help = "X509v3 CertificatePolicies entry in the form <oid>[;cps=<uri>][;notice=<text>]"
+    )]
+    pub policy: Vec<PolicySpec>,
+    #[clap(long, value_hint = ValueHint::Url, help = "X509v3 CRLDistributionPoints URI")]
+    pub crl_url: Vec<String>,
+    #[clap(long, value_hint = ValueHint::Url, help = "AuthorityInfoAccess OCSP responder URI")]
+    pub ocsp_url: Vec<String>,
+    #[clap(long, value_hint = ValueHint::Url, help = "AuthorityInfoAccess caIssuers URI")]
+    pub ca_issuers_url: Vec<String>,
+    #[clap(long, value_hint = ValueHint::Url, help = "SubjectInfoAccess caRepository URI")]
+    pub ca_repository_url: Vec<String>,
+    #[clap(long, value_hint = ValueHint::Url, help = "SubjectInfoAccess timeStamping URI")]
+    pub timestamping_url: Vec<String>,
+    #[clap(
+        long,
+        help = "Add the OCSP no-check extension for delegated OCSP responders"
+    )]
+    pub ocsp_no_check: bool,
+    #[clap(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "RFC 7633 TLS Feature extension values, status-request requests OCSP must-staple"
+    )]
+    pub tls_features: Option<Vec<TlsFeature>>,
+    #[clap(
+        long,
+        value_hint = ValueHint::Other,
+        help = "QCStatement, one of compliance, sscd, retention:<years>, type:<esign|eseal|web>, pds:<lang>:<url> or psd2:<roles>;<NCA name>;<NCA id>"
+    )]
+    pub qc_statement: Vec<QcSpec>,
+    #[clap(
+        long,
+        value_enum,
+        help = "Active Directory preset, sets default key usages and checks the SubjectAltName"
+    )]
+    pub profile: Option<Profile>,
+    #[clap(
+        long,
+        value_hint = ValueHint::Other,
+        help = "Microsoft certificate template in the form <oid>[:<major>[:<minor>]], major defaults to 100"
+    )]
+    pub template: Option<TemplateSpec>,
+    #[clap(
+        long,
+        value_hint = ValueHint::Other,
+        help = "Account SID for Active Directory strong certificate mapping, e.g. S-1-5-21-...-1104"
+    )]
+    pub sid: Option<String>,
+    #[clap(
+        long,
+        value_hint = ValueHint::Other,
+        help = "Arbitrary extension in the form <oid>:<critical|noncritical>:<hex|base64>:<DER value>"
+    )]
+    pub extension: Vec<RawExtension>,
+    #[clap(
+        long,
+        help = "Issue the certificate even if its extensions are inconsistent, reporting the problems as warnings"
+    )]
+    pub allow_inconsistent: bool,
 }
 
 #[derive(Debug, Subcommand)]
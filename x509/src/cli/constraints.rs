@@ -0,0 +1,89 @@
+use std::{net::IpAddr, str::FromStr};
+
+use color_eyre::Result;
+use x509_util::prelude::{Context, GeneralSubtree};
+
+use crate::name::OwnedName;
+
+/// A NameConstraints subtree given on the command line, e.g. `dns:example.com` or `ip:10.0.0.0/8`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameSubtree {
+    Dns(String),
+    Ip(IpAddr, u8),
+    Email(String),
+    Uri(String),
+    DirName(OwnedName),
+}
+
+impl NameSubtree {
+    pub fn to_general_subtree<'a>(&'a self, context: &'a Context) -> Result<GeneralSubtree<'a>> {
+        Ok(match self {
+            NameSubtree::Dns(dns) => GeneralSubtree::DnsName(dns),
+            NameSubtree::Ip(ip, prefix) => GeneralSubtree::IpNetwork(*ip, *prefix),
+            NameSubtree::Email(email) => GeneralSubtree::Rfc822Name(email),
+            NameSubtree::Uri(uri) => GeneralSubtree::Uri(uri),
+            NameSubtree::DirName(name) => {
+                GeneralSubtree::DirectoryName(name.to_x509_name(context)?)
+            }
+        })
+    }
+}
+
+impl FromStr for NameSubtree {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Expected <type>:<value>, got `{}`", s))?;
+
+        match kind.to_ascii_lowercase().as_str() {
+            "dns" => Ok(NameSubtree::Dns(value.to_string())),
+            "ip" => {
+                let (ip, prefix) = value
+                    .split_once('/')
+                    .ok_or_else(|| format!("Expected <ip>/<prefix>, got `{}`", value))?;
+                let ip: IpAddr = ip
+                    .parse()
+                    .map_err(|e| format!("Invalid IP address `{}`: {}", ip, e))?;
+                let prefix: u8 = prefix
+                    .parse()
+                    .map_err(|e| format!("Invalid prefix length `{}`: {}", prefix, e))?;
+
+                let max = if ip.is_ipv4() { 32 } else { 128 };
+                if prefix > max {
+                    return Err(format!("Prefix length `{}` exceeds {}", prefix, max));
+                }
+
+                Ok(NameSubtree::Ip(ip, prefix))
+            }
+            "email" => Ok(NameSubtree::Email(value.to_string())),
+            "uri" => Ok(NameSubtree::Uri(value.to_string())),
+            "dirname" => Ok(NameSubtree::DirName(value.parse()?)),
+            _ => Err(format!("Unknown NameConstraints subtree type `{}`", kind)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NameSubtree;
+
+    #[test]
+    fn parses_right() {
+        assert_eq!(
+            "dns:.example.com".parse(),
+            Ok(NameSubtree::Dns(".example.com".to_string()))
+        );
+        assert!(matches!(
+            "ip:10.0.0.0/8".parse::<NameSubtree>(),
+            Ok(NameSubtree::Ip(_, 8))
+        ));
+        assert!(matches!(
+            "ip:fd00::/8".parse::<NameSubtree>(),
+            Ok(NameSubtree::Ip(ip, 8)) if ip.is_ipv6()
+        ));
+        assert!("ip:10.0.0.0".parse::<NameSubtree>().is_err());
+        assert!("ip:10.0.0.0/33".parse::<NameSubtree>().is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use std::str::FromStr;
+
+use base64::Engine;
+use x509_util::x509_cert::der::asn1::ObjectIdentifier;
+
+/// An arbitrary extension given on the command line, in the form
+/// `<oid>:<critical|noncritical>:<hex|base64>:<DER value>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawExtension {
+    pub oid: ObjectIdentifier,
+    pub critical: bool,
+    pub value: Vec<u8>,
+}
+
+impl FromStr for RawExtension {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(4, ':');
+
+        let (Some(oid), Some(critical), Some(encoding), Some(value)) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(format!(
+                "Expected <oid>:<critical|noncritical>:<hex|base64>:<value>, got `{}`",
+                s
+            ));
+        };
+
+        let oid = ObjectIdentifier::new(oid)
+            .map_err(|e| format!("Invalid extension OID `{}`: {}", oid, e))?;
+
+        let critical = match critical {
+            "critical" | "true" => true,
+            "noncritical" | "false" => false,
+            _ => return Err(format!("Invalid criticality `{}`", critical)),
+        };
+
+        let value = match encoding {
+            "hex" => hex::decode(value).map_err(|e| e.to_string())?,
+            "base64" => base64::engine::general_purpose::STANDARD
+                .decode(value)
+                .map_err(|e| e.to_string())?,
+            _ => return Err(format!("Unknown value encoding `{}`", encoding)),
+        };
+
+        Ok(RawExtension {
+            oid,
+            critical,
+            value,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RawExtension;
+
+    #[test]
+    fn parses_right() {
+        let ext: RawExtension = "1.3.6.1.5.5.7.48.1.5:noncritical:hex:0500".parse().unwrap();
+        assert!(!ext.critical);
+        assert_eq!(ext.value, [0x05, 0x00]);
+
+        let ext: RawExtension = "1.2.3.4:critical:base64:BQA=".parse().unwrap();
+        assert!(ext.critical);
+        assert_eq!(ext.value, [0x05, 0x00]);
+
+        assert!("1.2.3.4:critical:0500".parse::<RawExtension>().is_err());
+        assert!("1.2.3.4:maybe:hex:0500".parse::<RawExtension>().is_err());
+        assert!("1.2.3.4:critical:hex:zz".parse::<RawExtension>().is_err());
+    }
+}
@@ -0,0 +1,24 @@
+use x509_util::subject_public_key_info::KeyIdentifierMethod;
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum KeyIdMethod {
+    Rfc5280Method1,
+    Rfc5280Method2,
+    Rfc7093Method1,
+    Rfc7093Method2,
+    Rfc7093Method3,
+    Sha256,
+}
+
+impl From<KeyIdMethod> for KeyIdentifierMethod {
+    fn from(method: KeyIdMethod) -> Self {
+        match method {
+            KeyIdMethod::Rfc5280Method1 => KeyIdentifierMethod::Rfc5280Method1,
+            KeyIdMethod::Rfc5280Method2 => KeyIdentifierMethod::Rfc5280Method2,
+            KeyIdMethod::Rfc7093Method1 => KeyIdentifierMethod::Rfc7093Method1,
+            KeyIdMethod::Rfc7093Method2 => KeyIdentifierMethod::Rfc7093Method2,
+            KeyIdMethod::Rfc7093Method3 => KeyIdentifierMethod::Rfc7093Method3,
+            KeyIdMethod::Sha256 => KeyIdentifierMethod::Sha256,
+        }
+    }
+}
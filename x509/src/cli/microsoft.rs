@@ -0,0 +1,143 @@
+use std::str::FromStr;
+
+use x509_util::{
+    const_oid::db::rfc5912::RSA_ENCRYPTION, extensions::microsoft::CertificateTemplate,
+    x509_cert::der::asn1::ObjectIdentifier,
+};
+
+use super::{ExtKeyUsage, KeyUsage, SubjectAltName};
+
+/// Presets for certificates used by Active Directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Profile {
+    /// User certificate for smart card logon, requires a `upn:` SubjectAltName
+    Smartcard,
+    /// Domain controller certificate for LDAPS and PKINIT, requires a `dns:` SubjectAltName
+    DomainController,
+}
+
+impl Profile {
+    /// KeyUsage used when `--usages` is not given. Smart card logon only signs, a domain
+    /// controller with an RSA key also needs keyEncipherment for RSA key exchange in LDAPS.
+    pub fn usages(&self, key_algorithm: ObjectIdentifier) -> Vec<KeyUsage> {
+        match self {
+            Profile::DomainController if key_algorithm == RSA_ENCRYPTION => {
+                vec![KeyUsage::DigitalSignature, KeyUsage::KeyEncipherment]
+            }
+            Profile::Smartcard | Profile::DomainController => vec![KeyUsage::DigitalSignature],
+        }
+    }
+
+    /// ExtendedKeyUsage used when `--ext-usages` is not given
+    pub fn ext_usages(&self) -> Vec<ExtKeyUsage> {
+        match self {
+            Profile::Smartcard => vec![ExtKeyUsage::ClientAuth, ExtKeyUsage::SmartcardLogon],
+            Profile::DomainController => vec![
+                ExtKeyUsage::ServerAuth,
+                ExtKeyUsage::ClientAuth,
+                ExtKeyUsage::SmartcardLogon,
+                ExtKeyUsage::KdcAuth,
+            ],
+        }
+    }
+
+    /// Checks the SubjectAltName entries the profile depends on are present
+    pub fn check_san(&self, san: &[SubjectAltName]) -> Result<(), String> {
+        match self {
+            Profile::Smartcard if !san.iter().any(|s| matches!(s, SubjectAltName::Upn(_))) => {
+                Err("The smartcard profile requires a upn: SubjectAltName".to_string())
+            }
+            Profile::DomainController
+                if !san.iter().any(|s| matches!(s, SubjectAltName::Dns(_))) =>
+            {
+                Err("The domain-controller profile requires a dns: SubjectAltName".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A certificate template given on the command line, in the form `<oid>[:<major>[:<minor>]]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemplateSpec(pub CertificateTemplate);
+
+impl FromStr for TemplateSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+
+        let template_id = parts.next().unwrap_or_default();
+        let template_id = ObjectIdentifier::new(template_id)
+            .map_err(|e| format!("Invalid template OID `{}`: {}", template_id, e))?;
+
+        let mut version = |name| {
+            parts
+                .next()
+                .map(|v| {
+                    v.parse::<u32>()
+                        .map_err(|e| format!("Invalid {} version `{}`: {}", name, v, e))
+                })
+                .transpose()
+        };
+
+        let major_version = version("major")?.unwrap_or(100);
+        let minor_version = version("minor")?;
+
+        if parts.next().is_some() {
+            return Err(format!("Expected <oid>[:<major>[:<minor>]], got `{}`", s));
+        }
+
+        Ok(TemplateSpec(CertificateTemplate {
+            template_id,
+            major_version,
+            minor_version,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use x509_util::const_oid::db::rfc5912::{ID_EC_PUBLIC_KEY, RSA_ENCRYPTION};
+
+    use super::{KeyUsage, Profile, TemplateSpec};
+
+    #[test]
+    fn parses_right() {
+        let spec: TemplateSpec = "1.3.6.1.4.1.311.21.8.1.2:101:3".parse().unwrap();
+
+        assert_eq!(spec.0.template_id.to_string(), "1.3.6.1.4.1.311.21.8.1.2");
+        assert_eq!(spec.0.major_version, 101);
+        assert_eq!(spec.0.minor_version, Some(3));
+
+        let spec: TemplateSpec = "1.3.6.1.4.1.311.21.8.1.2".parse().unwrap();
+        assert_eq!(spec.0.major_version, 100);
+        assert_eq!(spec.0.minor_version, None);
+
+        assert!("1.3.6.1.4.1.311.21.8.1.2:a"
+            .parse::<TemplateSpec>()
+            .is_err());
+        assert!("1.3.6.1.4.1.311.21.8.1.2:1:2:3"
+            .parse::<TemplateSpec>()
+            .is_err());
+        assert!("DomainController".parse::<TemplateSpec>().is_err());
+    }
+
+    #[test]
+    fn profile_usages() {
+        use KeyUsage::*;
+
+        assert!(matches!(
+            Profile::Smartcard.usages(RSA_ENCRYPTION)[..],
+            [DigitalSignature]
+        ));
+        assert!(matches!(
+            Profile::DomainController.usages(RSA_ENCRYPTION)[..],
+            [DigitalSignature, KeyEncipherment]
+        ));
+        assert!(matches!(
+            Profile::DomainController.usages(ID_EC_PUBLIC_KEY)[..],
+            [DigitalSignature]
+        ));
+    }
+}
@@ -0,0 +1,83 @@
+use std::str::FromStr;
+
+use x509_util::{
+    extensions::{CertificatePolicy, PolicyQualifier},
+    x509_cert::der::asn1::ObjectIdentifier,
+};
+
+/// A certificate policy given on the command line, in the form `<oid>[;cps=<uri>][;notice=<text>]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicySpec {
+    pub policy: ObjectIdentifier,
+    pub cps: Vec<String>,
+    pub notice: Option<String>,
+}
+
+impl PolicySpec {
+    pub fn to_certificate_policy(&self) -> CertificatePolicy<'_> {
+        let mut policy = CertificatePolicy::new(self.policy);
+
+        policy
+            .qualifiers
+            .extend(self.cps.iter().map(|uri| PolicyQualifier::CpsUri(uri)));
+
+        if let Some(ref notice) = self.notice {
+            policy.qualifiers.push(PolicyQualifier::UserNotice {
+                notice_ref: None,
+                explicit_text: Some(notice),
+            });
+        }
+
+        policy
+    }
+}
+
+impl FromStr for PolicySpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(';');
+
+        let policy = parts.next().unwrap_or_default();
+        let policy = ObjectIdentifier::new(policy)
+            .map_err(|e| format!("Invalid policy OID `{}`: {}", policy, e))?;
+
+        let mut spec = PolicySpec {
+            policy,
+            cps: Vec::new(),
+            notice: None,
+        };
+
+        for part in parts {
+            match part.split_once('=') {
+                Some(("cps", uri)) => spec.cps.push(uri.to_string()),
+                Some(("notice", text)) if spec.notice.is_none() => {
+                    spec.notice = Some(text.to_string())
+                }
+                _ => return Err(format!("Unexpected policy qualifier `{}`", part)),
+            }
+        }
+
+        Ok(spec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PolicySpec;
+
+    #[test]
+    fn parses_right() {
+        let spec: PolicySpec = "2.23.140.1.2.1;cps=https://example.com/cps;notice=Hello there"
+            .parse()
+            .unwrap();
+
+        assert_eq!(spec.policy.to_string(), "2.23.140.1.2.1");
+        assert_eq!(spec.cps, ["https://example.com/cps"]);
+        assert_eq!(spec.notice.as_deref(), Some("Hello there"));
+
+        assert!("2.23.140.1.2.1".parse::<PolicySpec>().is_ok());
+        assert!("2.23.140.1.2.1;foo=bar".parse::<PolicySpec>().is_err());
+        assert!("not-an-oid".parse::<PolicySpec>().is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use std::str::FromStr;
+
+use x509_util::extensions::{PdsLocation, Psd2Role, QcStatement, QcType};
+
+/// A QCStatement given on the command line, one of `compliance`, `sscd`, `retention:<years>`,
+/// `type:<esign|eseal|web>[,...]`, `pds:<language>:<url>` or
+/// `psd2:<PSP_AS|PSP_PI|PSP_AI|PSP_IC>[,...];<NCA name>;<NCA id>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QcSpec {
+    Compliance,
+    Sscd,
+    Retention(u32),
+    Type(Vec<QcType>),
+    Pds {
+        language: String,
+        url: String,
+    },
+    Psd2 {
+        roles: Vec<Psd2Role>,
+        nca_name: String,
+        nca_id: String,
+    },
+}
+
+impl QcSpec {
+    /// Converts all specs to statements, merging every `pds:` location into a single QcPDS
+    pub fn to_qc_statements(specs: &[QcSpec]) -> Vec<QcStatement<'_>> {
+        let mut statements = Vec::new();
+        let mut locations = Vec::new();
+
+        for spec in specs {
+            let statement = match spec {
+                QcSpec::Compliance => QcStatement::Compliance,
+                QcSpec::Sscd => QcStatement::Sscd,
+                QcSpec::Retention(years) => QcStatement::RetentionPeriod(*years),
+                QcSpec::Type(types) => QcStatement::Type(types.clone()),
+                QcSpec::Pds { language, url } => {
+                    if locations.is_empty() {
+                        statements.push(QcStatement::Pds(Vec::new()));
+                    }
+
+                    locations.push(PdsLocation { url, language });
+                    continue;
+                }
+                QcSpec::Psd2 {
+                    roles,
+                    nca_name,
+                    nca_id,
+                } => QcStatement::Psd2 {
+                    roles: roles.clone(),
+                    nca_name,
+                    nca_id,
+                },
+            };
+
+            statements.push(statement);
+        }
+
+        for statement in statements.iter_mut() {
+            if let QcStatement::Pds(ref mut pds) = statement {
+                *pds = std::mem::take(&mut locations);
+            }
+        }
+
+        statements
+    }
+}
+
+fn parse_qc_type(s: &str) -> Result<QcType, String> {
+    match s.to_ascii_lowercase().as_str() {
+        "esign" => Ok(QcType::ESign),
+        "eseal" => Ok(QcType::ESeal),
+        "web" => Ok(QcType::Web),
+        _ => Err(format!("Unknown QcType `{}`", s)),
+    }
+}
+
+fn parse_psd2_role(s: &str) -> Result<Psd2Role, String> {
+    match s.to_ascii_uppercase().as_str() {
+        "PSP_AS" => Ok(Psd2Role::AccountServicing),
+        "PSP_PI" => Ok(Psd2Role::PaymentInitiation),
+        "PSP_AI" => Ok(Psd2Role::AccountInformation),
+        "PSP_IC" => Ok(Psd2Role::IssuingCardBased),
+        _ => Err(format!("Unknown PSD2 role `{}`", s)),
+    }
+}
+
+impl FromStr for QcSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s.split_once(':').unwrap_or((s, ""));
+
+        match (kind.to_ascii_lowercase().as_str(), value) {
+            ("compliance", "") => Ok(QcSpec::Compliance),
+            ("sscd", "") => Ok(QcSpec::Sscd),
+            ("retention", years) => years
+                .parse()
+                .map(QcSpec::Retention)
+                .map_err(|e| format!("Invalid retention period `{}`: {}", years, e)),
+            ("type", types) => Ok(QcSpec::Type(
+                types
+                    .split(',')
+                    .map(parse_qc_type)
+                    .collect::<Result<_, _>>()?,
+            )),
+            ("pds", location) => {
+                let (language, url) = location
+                    .split_once(':')
+                    .ok_or_else(|| format!("Expected pds:<language>:<url>, got `{}`", s))?;
+
+                Ok(QcSpec::Pds {
+                    language: language.to_string(),
+                    url: url.to_string(),
+                })
+            }
+            ("psd2", psd2) => match psd2.splitn(3, ';').collect::<Vec<_>>()[..] {
+                [roles, nca_name, nca_id] => Ok(QcSpec::Psd2 {
+                    roles: roles
+                        .split(',')
+                        .map(parse_psd2_role)
+                        .collect::<Result<_, _>>()?,
+                    nca_name: nca_name.to_string(),
+                    nca_id: nca_id.to_string(),
+                }),
+                _ => Err(format!(
+                    "Expected psd2:<roles>;<NCA name>;<NCA id>, got `{}`",
+                    s
+                )),
+            },
+            _ => Err(format!("Unknown QCStatement `{}`", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use x509_util::extensions::{Psd2Role, QcStatement, QcType};
+
+    use super::QcSpec;
+
+    #[test]
+    fn parses_right() {
+        assert_eq!("compliance".parse(), Ok(QcSpec::Compliance));
+        assert_eq!("retention:15".parse(), Ok(QcSpec::Retention(15)));
+        assert_eq!(
+            "type:eseal,web".parse(),
+            Ok(QcSpec::Type(vec![QcType::ESeal, QcType::Web]))
+        );
+        assert_eq!(
+            "psd2:PSP_PI,psp_ai;Financial Conduct Authority;GB-FCA".parse(),
+            Ok(QcSpec::Psd2 {
+                roles: vec![Psd2Role::PaymentInitiation, Psd2Role::AccountInformation],
+                nca_name: "Financial Conduct Authority".to_string(),
+                nca_id: "GB-FCA".to_string(),
+            })
+        );
+        assert!("compliance:yes".parse::<QcSpec>().is_err());
+        assert!("type:foo".parse::<QcSpec>().is_err());
+        assert!("psd2:PSP_PI;GB-FCA".parse::<QcSpec>().is_err());
+
+        let specs: Vec<QcSpec> = [
+            "pds:en:https://example.com/en.pdf",
+            "compliance",
+            "pds:de:https://example.com/de.pdf",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        let statements = QcSpec::to_qc_statements(&specs);
+
+        assert_eq!(statements.len(), 2);
+        assert!(matches!(&statements[0], QcStatement::Pds(l) if l.len() == 2));
+    }
+}
@@ -0,0 +1,110 @@
+use std::{net::IpAddr, str::FromStr};
+
+use color_eyre::Result;
+use x509_util::{
+    context::Alloc,
+    prelude::{Context, GeneralName},
+    x509_cert::der::{
+        asn1::{ObjectIdentifier, Utf8StringRef},
+        Encode,
+    },
+};
+
+use crate::name::OwnedName;
+
+/// A SubjectAltName entry given on the command line, e.g. `dns:example.com` or `ip:10.0.0.1`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubjectAltName {
+    Dns(String),
+    Ip(IpAddr),
+    Email(String),
+    Uri(String),
+    /// Only UTF8String values are supported, in the form `othername:<oid>;UTF8:<value>`
+    OtherName(ObjectIdentifier, String),
+    /// A Microsoft user principal name, e.g. `upn:user@corp.example.com`
+    Upn(String),
+    DirName(OwnedName),
+}
+
+impl SubjectAltName {
+    pub fn to_general_name<'a>(&'a self, context: &'a Context) -> Result<GeneralName<'a>> {
+        Ok(match self {
+            SubjectAltName::Dns(dns) => GeneralName::DnsName(dns),
+            SubjectAltName::Ip(ip) => GeneralName::IpAddress(*ip),
+            SubjectAltName::Email(email) => GeneralName::Rfc822Name(email),
+            SubjectAltName::Uri(uri) => GeneralName::Uri(uri),
+            SubjectAltName::OtherName(type_id, value) => GeneralName::OtherName {
+                type_id: *type_id,
+                value: Utf8StringRef::new(value)?.to_vec()?.alloc_into(context),
+            },
+            SubjectAltName::Upn(upn) => GeneralName::user_principal_name(context, upn)?,
+            SubjectAltName::DirName(name) => {
+                GeneralName::DirectoryName(name.to_x509_name(context)?)
+            }
+        })
+    }
+}
+
+impl FromStr for SubjectAltName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Expected <type>:<value>, got `{}`", s))?;
+
+        match kind.to_ascii_lowercase().as_str() {
+            "dns" => Ok(SubjectAltName::Dns(value.to_string())),
+            "ip" => value
+                .parse()
+                .map(SubjectAltName::Ip)
+                .map_err(|e| format!("Invalid IP address `{}`: {}", value, e)),
+            "email" => Ok(SubjectAltName::Email(value.to_string())),
+            "uri" => Ok(SubjectAltName::Uri(value.to_string())),
+            "othername" => {
+                let (oid, value) = value
+                    .split_once(';')
+                    .ok_or_else(|| format!("Expected <oid>;UTF8:<value>, got `{}`", value))?;
+                let oid = ObjectIdentifier::new(oid).map_err(|e| e.to_string())?;
+
+                match value.split_once(':') {
+                    Some((ty, value)) if ty.eq_ignore_ascii_case("utf8") => {
+                        Ok(SubjectAltName::OtherName(oid, value.to_string()))
+                    }
+                    _ => Err(format!("Unsupported otherName value `{}`", value)),
+                }
+            }
+            "upn" => Ok(SubjectAltName::Upn(value.to_string())),
+            "dirname" => Ok(SubjectAltName::DirName(value.parse()?)),
+            _ => Err(format!("Unknown SubjectAltName type `{}`", kind)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SubjectAltName;
+
+    #[test]
+    fn parses_right() {
+        assert_eq!(
+            "dns:example.com".parse(),
+            Ok(SubjectAltName::Dns("example.com".to_string()))
+        );
+        assert!(matches!(
+            "IP:::1".parse::<SubjectAltName>(),
+            Ok(SubjectAltName::Ip(ip)) if ip.is_ipv6()
+        ));
+        assert!(matches!(
+            "othername:1.3.6.1.4.1.311.20.2.3;UTF8:user@example.com".parse::<SubjectAltName>(),
+            Ok(SubjectAltName::OtherName(_, v)) if v == "user@example.com"
+        ));
+        assert_eq!(
+            "upn:user@corp.example.com".parse(),
+            Ok(SubjectAltName::Upn("user@corp.example.com".to_string()))
+        );
+        assert!("dirname:C=US,CN=foo".parse::<SubjectAltName>().is_ok());
+        assert!("ip:example.com".parse::<SubjectAltName>().is_err());
+        assert!("example.com".parse::<SubjectAltName>().is_err());
+    }
+}
@@ -1,4 +1,9 @@
-use x509_util::x509_cert::ext::pkix::KeyUsages;
+use std::str::FromStr;
+
+use x509_util::{
+    extensions::KeyPurpose,
+    x509_cert::{der::asn1::ObjectIdentifier, ext::pkix::KeyUsages},
+};
 
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
 pub enum KeyUsage {
@@ -44,3 +49,83 @@ impl From<&KeyUsage> for KeyUsages {
         }
     }
 }
+
+/// A key purpose given on the command line, one of the names below or a dotted OID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExtKeyUsage {
+    ServerAuth,
+    ClientAuth,
+    CodeSigning,
+    EmailProtection,
+    TimeStamping,
+    OCSPSigning,
+    SmartcardLogon,
+    KdcAuth,
+    Other(ObjectIdentifier),
+}
+
+impl FromStr for ExtKeyUsage {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "server-auth" => Ok(ExtKeyUsage::ServerAuth),
+            "client-auth" => Ok(ExtKeyUsage::ClientAuth),
+            "code-signing" => Ok(ExtKeyUsage::CodeSigning),
+            "email-protection" => Ok(ExtKeyUsage::EmailProtection),
+            "time-stamping" => Ok(ExtKeyUsage::TimeStamping),
+            "ocsp-signing" => Ok(ExtKeyUsage::OCSPSigning),
+            "smartcard-logon" => Ok(ExtKeyUsage::SmartcardLogon),
+            "kdc-auth" => Ok(ExtKeyUsage::KdcAuth),
+            _ => ObjectIdentifier::new(s)
+                .map(ExtKeyUsage::Other)
+                .map_err(|_| {
+                    format!(
+                        "Unknown ExtendedKeyUsage `{}`, expected a name or an OID",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+impl From<&ExtKeyUsage> for KeyPurpose {
+    fn from(usage: &ExtKeyUsage) -> Self {
+        match usage {
+            ExtKeyUsage::ServerAuth => KeyPurpose::ServerAuth,
+            ExtKeyUsage::ClientAuth => KeyPurpose::ClientAuth,
+            ExtKeyUsage::CodeSigning => KeyPurpose::CodeSigning,
+            ExtKeyUsage::EmailProtection => KeyPurpose::EmailProtection,
+            ExtKeyUsage::TimeStamping => KeyPurpose::TimeStamping,
+            ExtKeyUsage::OCSPSigning => KeyPurpose::OcspSigning,
+            ExtKeyUsage::SmartcardLogon => KeyPurpose::SmartcardLogon,
+            ExtKeyUsage::KdcAuth => KeyPurpose::KdcAuth,
+            ExtKeyUsage::Other(oid) => KeyPurpose::from(*oid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use x509_util::{extensions::KeyPurpose, x509_cert::der::asn1::ObjectIdentifier};
+
+    use super::ExtKeyUsage;
+
+    #[test]
+    fn parses_right() {
+        assert_eq!("server-auth".parse(), Ok(ExtKeyUsage::ServerAuth));
+        assert_eq!("KDC-Auth".parse(), Ok(ExtKeyUsage::KdcAuth));
+
+        let usage: ExtKeyUsage = "1.3.6.1.4.1.311.10.3.12".parse().unwrap();
+        assert_eq!(
+            usage,
+            ExtKeyUsage::Other(ObjectIdentifier::new_unwrap("1.3.6.1.4.1.311.10.3.12"))
+        );
+
+        // Well-known purposes given by OID map to their named variant
+        let usage: ExtKeyUsage = "1.3.6.1.5.5.7.3.1".parse().unwrap();
+        assert_eq!(KeyPurpose::from(&usage), KeyPurpose::ServerAuth);
+
+        assert!("server".parse::<ExtKeyUsage>().is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use color_eyre::Result;
+use lalrpop_util::{lexer::Token, ParseError};
 use std::{fmt::Display, str::FromStr};
 use x509_util::prelude::Context;
 
@@ -7,15 +8,16 @@ lalrpop_mod!(
     name_parser
 );
 
+/// RDNs in the order given, each holding one or more key/value pairs
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct OwnedName(pub Vec<(String, String)>);
+pub struct OwnedName(pub Vec<Vec<(String, String)>>);
 
 impl OwnedName {
     pub fn to_x509_name<'a>(
         &self,
         context: &'a Context,
     ) -> Result<x509_util::x509_cert::name::Name<'a>> {
-        Ok(x509_util::name::Name::from_pairs(context, &self.0)?)
+        Ok(x509_util::name::Name::from_rdns(context, &self.0)?)
     }
 }
 
@@ -25,24 +27,88 @@ impl FromStr for OwnedName {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let result = name_parser::NameParser::new()
             .parse(s)
-            .map_err(|e| e.to_string())?;
+            .map_err(|e| describe_error(s, e))?;
 
         let cont = result
-            .iter()
-            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .into_iter()
+            .map(|rdn| rdn.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
             .collect();
 
         Ok(OwnedName(cont))
     }
 }
 
+/// Resolves backslash escapes, `\,` stands for `,`
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => out.extend(chars.next()),
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+fn describe_error(s: &str, e: ParseError<usize, Token<'_>, &'static str>) -> String {
+    let column = |location: usize| s[..location].chars().count() + 1;
+
+    let expected = |expected: Vec<String>| {
+        let mut names: Vec<_> = expected
+            .iter()
+            .map(|e| match e.as_str() {
+                "KEY" | "OID" => "an attribute type",
+                "UNQUOTED" | "QUOTED" => "a value",
+                e => e,
+            })
+            .collect();
+        names.dedup();
+
+        names.join(" or ")
+    };
+
+    match e {
+        ParseError::InvalidToken { location } => {
+            format!(
+                "Invalid name at column {}, unterminated quote?",
+                column(location)
+            )
+        }
+        ParseError::UnrecognizedEOF { expected: e, .. } => {
+            format!("Unexpected end of name, expected {}", expected(e))
+        }
+        ParseError::UnrecognizedToken {
+            token: (start, token, _),
+            expected: e,
+        } => format!(
+            "Unexpected `{}` at column {}, expected {}",
+            token,
+            column(start),
+            expected(e)
+        ),
+        ParseError::ExtraToken {
+            token: (start, token, _),
+        } => format!("Unexpected `{}` at column {}", token, column(start)),
+        ParseError::User { error } => error.to_string(),
+    }
+}
+
 impl Display for OwnedName {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut iter = self.0.iter().peekable();
-        while let Some((k, v)) = iter.next() {
-            f.write_str(k)?;
-            f.write_str(" = ")?;
-            f.write_str(v)?;
+        while let Some(rdn) = iter.next() {
+            for (i, (k, v)) in rdn.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(" + ")?;
+                }
+
+                f.write_str(k)?;
+                f.write_str(" = ")?;
+                f.write_str(v)?;
+            }
 
             // If peek gives us a Some, there's more elements to process
             if iter.peek().is_some() {
@@ -68,5 +134,40 @@ mod tests {
     fn parses_right() {
         test_okay("C=US,ST = CA, L =SF");
         test_okay("C=US,ST = \"CA Minor\", L =SF");
+        test_okay("DC=com,DC=example,emailAddress=admin,2.5.4.5=1234");
+        test_okay("DC=com,CN=foo+UID=123");
+
+        let name: super::OwnedName = "C=US,CN=foo + UID=123".parse().unwrap();
+        assert_eq!(name.0.len(), 2);
+        assert_eq!(name.0[1].len(), 2);
+
+        let name: super::OwnedName = "O=\"Acme, Inc.\", CN=*.example.com, L=São Paulo, \
+                                      emailAddress=a@b.c, OU=R\\&D, ou=a\\=b\\,c, \
+                                      2.5.4.97=VATGB-123"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            name.0,
+            [
+                ("O", "Acme, Inc."),
+                ("CN", "*.example.com"),
+                ("L", "São Paulo"),
+                ("emailAddress", "a@b.c"),
+                ("OU", "R&D"),
+                ("ou", "a=b,c"),
+                ("2.5.4.97", "VATGB-123"),
+            ]
+            .map(|(k, v)| vec![(k.to_string(), v.to_string())])
+        );
+
+        assert_eq!(
+            "C=US,CN=a,b".parse::<super::OwnedName>(),
+            Err("Unexpected end of name, expected \"=\"".to_string())
+        );
+        assert_eq!(
+            "C=US,CN=a=b".parse::<super::OwnedName>(),
+            Err("Unexpected `=` at column 10, expected \"+\" or \",\"".to_string())
+        );
+        assert!("CN=\"unterminated".parse::<super::OwnedName>().is_err());
     }
 }